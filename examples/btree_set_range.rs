@@ -1,55 +1,64 @@
 use std::collections::BTreeSet;
 use std::ops::Bound::{self, *};
+use std::ops::RangeBounds;
 
-use quickcheck::{quickcheck, TestResult};
+use quickcheck::quickcheck;
+use quickcheck::wrappers::{
+    NonEmptyRange, OrderedRange, OrderedRangeInclusive, SortedRange,
+};
+
+// `BTreeSet::range` panics if the start of a range is greater than its end.
+// Taking sorted ranges means that no generated test needs to be discarded.
 
 /// Covers every `std::ops::Range*` plus variants with exclusive start.
 type RangeAny<T> = (Bound<T>, Bound<T>);
 
-/// Mimic `RangeBounds::contains`, stabilized in Rust 1.35.
-trait RangeBounds<T> {
-    fn contains(&self, _: &T) -> bool;
+/// Checks that `BTreeSet::range` returns all items contained in the given
+/// `range`.
+fn check_range(set: BTreeSet<i32>, range: OrderedRange<i32>) -> bool {
+    let SortedRange(range) = range;
+    let xs: BTreeSet<_> = set.range(range.clone()).cloned().collect();
+    set.iter().all(|x| range.contains(x) == xs.contains(x))
 }
 
-impl<T: PartialOrd> RangeBounds<T> for RangeAny<T> {
-    fn contains(&self, item: &T) -> bool {
-        (match &self.0 {
-            Included(start) => start <= item,
-            Excluded(start) => start < item,
-            Unbounded => true,
-        }) && (match &self.1 {
-            Included(end) => item <= end,
-            Excluded(end) => item < end,
-            Unbounded => true,
-        })
-    }
+/// Checks the same for inclusive ranges.
+fn check_range_inclusive(
+    set: BTreeSet<i32>,
+    range: OrderedRangeInclusive<i32>,
+) -> bool {
+    let SortedRange(range) = range;
+    let xs: BTreeSet<_> = set.range(range.clone()).cloned().collect();
+    set.iter().all(|x| range.contains(x) == xs.contains(x))
 }
 
-/// Checks conditions where `BTreeSet::range` panics:
-/// - Panics if range start > end.
-/// - Panics if range start == end and both bounds are Excluded.
-fn panics<T: PartialOrd>(range: RangeAny<T>) -> bool {
-    match (&range.0, &range.1) {
-        (Excluded(start), Excluded(end)) => start >= end,
-        (Included(start), Excluded(end))
-        | (Excluded(start), Included(end))
-        | (Included(start), Included(end)) => start > end,
-        (Unbounded, _) | (_, Unbounded) => false,
+/// Replaces the value of `bound`, if any, with `value`.
+fn bound_at(bound: Bound<()>, value: i32) -> Bound<i32> {
+    match bound {
+        Included(()) => Included(value),
+        Excluded(()) => Excluded(value),
+        Unbounded => Unbounded,
     }
 }
 
-/// Checks that `BTreeSet::range` returns all items contained in the given `range`.
-fn check_range(set: BTreeSet<i32>, range: RangeAny<i32>) -> TestResult {
-    if panics(range) {
-        TestResult::discard()
-    } else {
-        let xs: BTreeSet<_> = set.range(range).cloned().collect();
-        TestResult::from_bool(
-            set.iter().all(|x| range.contains(x) == xs.contains(x)),
-        )
-    }
+/// Checks the same for any pair of bounds.
+///
+/// The kinds of the bounds are generated separately from their values,
+/// which are sorted and distinct, since `BTreeSet::range` also panics if
+/// both bounds exclude the same value.
+fn check_bounds(
+    set: BTreeSet<i32>,
+    values: NonEmptyRange<i32>,
+    kinds: (Bound<()>, Bound<()>),
+) -> bool {
+    let NonEmptyRange(values) = values;
+    let range: RangeAny<i32> =
+        (bound_at(kinds.0, values.start), bound_at(kinds.1, values.end));
+    let xs: BTreeSet<_> = set.range(range).cloned().collect();
+    set.iter().all(|x| range.contains(x) == xs.contains(x))
 }
 
 fn main() {
-    quickcheck(check_range as fn(_, _) -> TestResult);
+    quickcheck(check_range as fn(_, _) -> bool);
+    quickcheck(check_range_inclusive as fn(_, _) -> bool);
+    quickcheck(check_bounds as fn(_, _, _) -> bool);
 }
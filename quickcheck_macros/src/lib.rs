@@ -6,50 +6,152 @@ extern crate syn;
 use std::mem;
 
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote, quote_spanned};
 use syn::{
-    parse::{Parse, Parser},
+    parse::{Parse, ParseStream, Parser},
     parse_quote,
     spanned::Spanned,
 };
 
+/// The error reported for functions that `#[quickcheck]` can't turn into a
+/// property.
+const UNSUPPORTED_FN: &str = "#[quickcheck] can only be applied to simple \
+     free functions with typed parameters, no defaults, self, or variadic \
+     arguments";
+
+/// The error reported for methods, which can't be properties since every
+/// argument of a property is generated.
+const SELF_RECEIVER: &str = "#[quickcheck] can't be applied to methods \
+     that take `self`, since quickcheck generates every argument of a \
+     property; take the value as a typed parameter of a free function \
+     instead";
+
+/// Turns a function into a test of the property it implements.
+///
+/// An argument can be generated by a custom generator instead of its
+/// `Arbitrary` impl with `#[generator(path)]`, where `path` is a
+/// `fn(&mut Gen) -> T` and `T` is the type of the argument. Generators of
+/// any other type are rejected:
+///
+/// ```compile_fail,E0308
+/// extern crate quickcheck;
+/// extern crate quickcheck_macros;
+///
+/// use quickcheck::Gen;
+/// use quickcheck_macros::quickcheck;
+///
+/// fn small(g: &mut Gen) -> u32 {
+///     u32::from(g.choose(&[1u8, 2, 3]).copied().unwrap())
+/// }
+///
+/// #[quickcheck]
+/// fn small_is_small(#[generator(small)] x: u8) -> bool {
+///     x <= 3
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn quickcheck(_args: TokenStream, input: TokenStream) -> TokenStream {
     let output = match syn::Item::parse.parse(input.clone()) {
         Ok(syn::Item::Fn(mut item_fn)) => {
             let mut inputs = syn::punctuated::Punctuated::new();
+            let mut args = Vec::new();
             let mut errors = Vec::new();
 
-            item_fn.sig.inputs.iter().for_each(|input| match *input {
-                syn::FnArg::Typed(syn::PatType { ref ty, .. }) => {
-                    inputs.push(parse_quote!(_: #ty));
-                }
-                _ => errors.push(syn::parse::Error::new(
-                    input.span(),
-                    "unsupported kind of function argument",
-                )),
+            item_fn.sig.inputs.iter_mut().for_each(|input| match *input {
+                syn::FnArg::Typed(syn::PatType {
+                    ref mut attrs,
+                    ref ty,
+                    ..
+                }) => match take_generator(attrs) {
+                    Ok(generator) => {
+                        inputs.push(parse_quote!(_: #ty));
+                        args.push((ty.clone(), generator));
+                    }
+                    Err(err) => errors.push(err),
+                },
+                syn::FnArg::Receiver(_) => errors
+                    .push(syn::parse::Error::new(input.span(), SELF_RECEIVER)),
             });
+            if let Some(ref variadic) = item_fn.sig.variadic {
+                errors.push(syn::parse::Error::new(
+                    variadic.span(),
+                    UNSUPPORTED_FN,
+                ));
+            }
 
             if errors.is_empty() {
                 let attrs = mem::replace(&mut item_fn.attrs, Vec::new());
                 let name = &item_fn.sig.ident;
-                let fn_type = syn::TypeBareFn {
-                    lifetimes: None,
-                    unsafety: item_fn.sig.unsafety.clone(),
-                    abi: item_fn.sig.abi.clone(),
-                    fn_token: <syn::Token![fn]>::default(),
-                    paren_token: syn::token::Paren::default(),
-                    inputs,
-                    variadic: item_fn.sig.variadic.clone(),
-                    output: item_fn.sig.output.clone(),
-                };
 
-                quote! {
-                    #[test]
-                    #(#attrs)*
-                    fn #name() {
-                        #item_fn
-                       ::quickcheck::quickcheck(#name as #fn_type)
+                if args.iter().all(|(_, generator)| generator.is_none()) {
+                    let fn_type = syn::TypeBareFn {
+                        lifetimes: None,
+                        unsafety: item_fn.sig.unsafety.clone(),
+                        abi: item_fn.sig.abi.clone(),
+                        fn_token: <syn::Token![fn]>::default(),
+                        paren_token: syn::token::Paren::default(),
+                        inputs,
+                        variadic: item_fn.sig.variadic.clone(),
+                        output: item_fn.sig.output.clone(),
+                    };
+
+                    quote! {
+                        #[test]
+                        #(#attrs)*
+                        fn #name() {
+                            #item_fn
+                           ::quickcheck::quickcheck(#name as #fn_type)
+                        }
+                    }
+                } else {
+                    let output = &item_fn.sig.output;
+                    let mut wrappers = Vec::new();
+                    let mut checks = Vec::new();
+                    let mut prop_tys = Vec::new();
+                    let mut prop_params = Vec::new();
+                    let mut call_args = Vec::new();
+
+                    for (i, (ty, generator)) in args.iter().enumerate() {
+                        let arg = format_ident!("arg{}", i);
+                        match *generator {
+                            None => {
+                                prop_tys.push(quote!(#ty));
+                                prop_params.push(quote!(#arg: #ty));
+                                call_args.push(quote!(#arg));
+                            }
+                            Some(ref generator) => {
+                                let wrapper =
+                                    format_ident!("QuickCheckGenerated{}", i);
+                                wrappers.push(generator.wrapper(&wrapper, ty));
+                                checks.push(generator.check(ty));
+                                prop_tys.push(quote!(#wrapper));
+                                prop_params.push(quote!(#arg: #wrapper));
+                                call_args.push(quote!(#arg.0));
+                            }
+                        }
+                    }
+
+                    // The test function isn't compiled unless tests are,
+                    // so the generators are checked outside of it, too.
+                    let cfgs =
+                        attrs.iter().filter(|attr| attr.path.is_ident("cfg"));
+                    quote! {
+                        #(#cfgs)*
+                        #[cfg(not(test))]
+                        const _: () = { #(#checks)* };
+
+                        #[test]
+                        #(#attrs)*
+                        fn #name() {
+                            #item_fn
+                            #(#wrappers)*
+                            fn __quickcheck_prop(#(#prop_params),*) #output {
+                                #name(#(#call_args),*)
+                            }
+                            ::quickcheck::quickcheck(
+                                __quickcheck_prop as fn(#(#prop_tys),*) #output
+                            )
+                        }
                     }
                 }
             } else {
@@ -72,6 +174,12 @@ pub fn quickcheck(_args: TokenStream, input: TokenStream) -> TokenStream {
                 }
             }
         }
+        // An item that looks like a function but doesn't parse as one, e.g.,
+        // because a parameter has a default value.
+        Err(err) if is_fn(&input) => {
+            syn::parse::Error::new(err.span(), UNSUPPORTED_FN)
+                .to_compile_error()
+        }
         _ => {
             let span = proc_macro2::TokenStream::from(input).span();
             let msg =
@@ -83,3 +191,153 @@ pub fn quickcheck(_args: TokenStream, input: TokenStream) -> TokenStream {
 
     output.into()
 }
+
+/// Returns whether `input` contains the `fn` keyword outside of any
+/// delimited group, which is the case for function items.
+fn is_fn(input: &TokenStream) -> bool {
+    input.clone().into_iter().any(|tt| match tt {
+        proc_macro::TokenTree::Ident(ident) => ident.to_string() == "fn",
+        _ => false,
+    })
+}
+
+/// A custom generator for a single property argument, written as
+/// `#[generator(path)]` or `#[generator(path, filter = path)]`.
+///
+/// The generator must be a `fn(&mut Gen) -> T` and the filter, if present,
+/// must be a `fn(&T) -> bool`. Shrinking uses `T`'s own shrinker, with
+/// candidates rejected by the filter skipped.
+struct Generator {
+    generator: syn::Path,
+    filter: Option<syn::Path>,
+}
+
+impl Parse for Generator {
+    fn parse(input: ParseStream) -> syn::Result<Generator> {
+        let generator = input.parse()?;
+        let mut filter = None;
+        if input.parse::<Option<syn::Token![,]>>()?.is_some()
+            && !input.is_empty()
+        {
+            let key: syn::Ident = input.parse()?;
+            if key != "filter" {
+                return Err(syn::parse::Error::new(
+                    key.span(),
+                    "expected `filter = path`",
+                ));
+            }
+            input.parse::<syn::Token![=]>()?;
+            filter = Some(input.parse()?);
+            input.parse::<Option<syn::Token![,]>>()?;
+        }
+        Ok(Generator { generator, filter })
+    }
+}
+
+impl Generator {
+    /// Returns statements that fail to compile unless this generator, and
+    /// its filter, if any, have the right types for an argument of type
+    /// `ty`.
+    fn check(&self, ty: &syn::Type) -> proc_macro2::TokenStream {
+        let generator = &self.generator;
+        let mut check = quote_spanned! {generator.span()=>
+            let _: fn(&mut ::quickcheck::Gen) -> #ty = #generator;
+        };
+        if let Some(ref filter) = self.filter {
+            check.extend(quote_spanned! {filter.span()=>
+                let _: fn(&#ty) -> bool = #filter;
+            });
+        }
+        check
+    }
+
+    /// Returns the definition of a newtype named `wrapper` around `ty` whose
+    /// `Arbitrary` impl defers to this generator.
+    fn wrapper(
+        &self,
+        wrapper: &syn::Ident,
+        ty: &syn::Type,
+    ) -> proc_macro2::TokenStream {
+        let generator = &self.generator;
+        let generator = quote_spanned! {generator.span()=>
+            let generator: fn(&mut ::quickcheck::Gen) -> #ty = #generator;
+        };
+        let shrink = match self.filter {
+            None => quote! {
+                ::quickcheck::Arbitrary::shrink(&self.0).map(#wrapper)
+            },
+            Some(ref filter) => {
+                let filter = quote_spanned! {filter.span()=>
+                    let filter: fn(&#ty) -> bool = #filter;
+                };
+                quote! {{
+                    #filter
+                    ::quickcheck::Arbitrary::shrink(&self.0)
+                        .filter(filter)
+                        .map(#wrapper)
+                }}
+            }
+        };
+
+        quote! {
+            #[derive(Clone)]
+            struct #wrapper(#ty);
+
+            impl ::std::fmt::Debug for #wrapper {
+                fn fmt(
+                    &self,
+                    f: &mut ::std::fmt::Formatter,
+                ) -> ::std::fmt::Result {
+                    ::std::fmt::Debug::fmt(&self.0, f)
+                }
+            }
+
+            impl ::quickcheck::Arbitrary for #wrapper {
+                fn arbitrary(g: &mut ::quickcheck::Gen) -> #wrapper {
+                    #generator
+                    #wrapper(generator(g))
+                }
+
+                fn shrink(
+                    &self,
+                ) -> ::std::boxed::Box<dyn Iterator<Item = #wrapper>> {
+                    ::std::boxed::Box::new(#shrink)
+                }
+            }
+        }
+    }
+}
+
+/// Removes a `#[generator(...)]` attribute from `attrs`, if one exists, and
+/// returns it parsed.
+fn take_generator(
+    attrs: &mut Vec<syn::Attribute>,
+) -> syn::Result<Option<Generator>> {
+    let mut generator = None;
+    let mut error: Option<syn::parse::Error> = None;
+    attrs.retain(|attr| {
+        if !attr.path.is_ident("generator") {
+            return true;
+        }
+        let result = if generator.is_some() {
+            Err(syn::parse::Error::new(
+                attr.span(),
+                "duplicate #[generator] attribute",
+            ))
+        } else {
+            attr.parse_args::<Generator>()
+        };
+        match result {
+            Ok(parsed) => generator = Some(parsed),
+            Err(err) => match error {
+                None => error = Some(err),
+                Some(ref mut error) => error.combine(err),
+            },
+        }
+        false
+    });
+    match error {
+        None => Ok(generator),
+        Some(err) => Err(err),
+    }
+}
@@ -3,7 +3,7 @@
 extern crate quickcheck;
 extern crate quickcheck_macros;
 
-use quickcheck::TestResult;
+use quickcheck::{Arbitrary, Gen, TestResult};
 use quickcheck_macros::quickcheck;
 
 #[quickcheck]
@@ -17,7 +17,9 @@ fn min(x: isize, y: isize) -> TestResult {
 
 #[quickcheck]
 #[should_panic]
-fn fail_fn() -> bool { false }
+fn fail_fn() -> bool {
+    false
+}
 
 #[quickcheck]
 static static_bool: bool = true;
@@ -32,3 +34,37 @@ static fail_static_bool: bool = false;
 fn static_bool_test_is_function() {
     static_bool()
 }
+
+fn small_even(g: &mut Gen) -> u64 {
+    u64::from(u8::arbitrary(g)) * 2
+}
+
+fn is_even(n: &u64) -> bool {
+    n & 1 == 0
+}
+
+fn ascii_byte(g: &mut Gen) -> u8 {
+    u8::arbitrary(g) % 128
+}
+
+#[quickcheck]
+fn generator_is_honored(#[generator(small_even)] n: u64) -> bool {
+    is_even(&n) && n <= 510
+}
+
+#[quickcheck]
+fn generator_multiple_arguments(
+    #[generator(small_even, filter = is_even)] n: u64,
+    xs: Vec<u8>,
+    #[generator(ascii_byte)] b: u8,
+) -> bool {
+    is_even(&n) && b.is_ascii() && xs.len() < 1_000
+}
+
+#[quickcheck]
+#[should_panic]
+fn generator_shrinks_with_filter(
+    #[generator(small_even, filter = is_even)] n: u64,
+) -> bool {
+    n < 4
+}
@@ -1,28 +1,37 @@
+use std::any::{type_name, Any, TypeId};
+use std::array::TryFromSliceError;
+use std::borrow::Cow;
 use std::char;
+use std::char::CharTryFromError;
+use std::cmp::Reverse;
 use std::collections::{
     BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque,
 };
-use std::env;
+use std::convert::{Infallible, TryFrom};
 use std::ffi::{CString, OsString};
+use std::fmt;
 use std::hash::{BuildHasher, Hash};
 use std::iter::{empty, once};
+use std::mem::{self, MaybeUninit};
 use std::net::{
     IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6,
 };
-use std::num::Wrapping;
 use std::num::{
     NonZeroU128, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
 };
+use std::num::{TryFromIntError, Wrapping};
 use std::ops::{
-    Bound, Range, RangeFrom, RangeFull, RangeInclusive, RangeTo,
-    RangeToInclusive,
+    Bound, Deref, DerefMut, Neg, Range, RangeFrom, RangeFull, RangeInclusive,
+    RangeTo, RangeToInclusive,
 };
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::path::{PathBuf, MAIN_SEPARATOR};
+#[cfg(debug_assertions)]
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use rand::seq::SliceRandom;
-use rand::{self, Rng, SeedableRng};
+use rand::{self, Rng, RngCore, SeedableRng};
 
 /// Gen represents a PRNG.
 ///
@@ -34,8 +43,93 @@ use rand::{self, Rng, SeedableRng};
 /// It is unspecified whether this is a secure RNG or not. Therefore, callers
 /// should assume it is insecure.
 pub struct Gen {
-    rng: rand::rngs::SmallRng,
+    pub(crate) rng: rand::rngs::SmallRng,
+    /// The seed that `rng` was created from.
+    seed: u64,
     size: usize,
+    /// The number of times `size` has been consulted, so that tests can
+    /// check which `Arbitrary` impls depend on it. This is atomic so that
+    /// `Gen` is `Sync` in every build profile.
+    #[cfg(debug_assertions)]
+    size_reads: AtomicUsize,
+    none_ratio: f64,
+    err_ratio: f64,
+    /// The number of nested `Option`s whose contents are being generated.
+    option_depth: usize,
+    /// The type that `Option::arbitrary` is generating as the contents of
+    /// a `Some`, if it's generating one. An `Option` of this type is nested
+    /// directly in another one.
+    option_contents: Option<TypeId>,
+    /// Generators installed with `Gen::install`, keyed by the type they
+    /// generate. Each value is a `fn(&mut Gen) -> T`.
+    generators: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+    /// The sink set with `Gen::trace`, which is given every primitive draw.
+    tracer: Option<Mutex<Tracer>>,
+}
+
+/// The probability with which `Option::arbitrary` generates `None`, unless
+/// changed with `Gen::set_none_ratio`.
+const DEFAULT_NONE_RATIO: f64 = 0.25;
+
+/// The probability with which `Result::arbitrary` generates `Err`, unless
+/// changed with `Gen::set_err_ratio`.
+const DEFAULT_ERR_RATIO: f64 = 0.25;
+
+// `Gen` must stay `Send` and `Sync`, so that harnesses can move generators
+// to other threads, e.g., with `pool::GenPool`, and share them by reference.
+const _: () = {
+    const fn assert_send<T: Send>() {}
+    const fn assert_sync<T: Sync>() {}
+    assert_send::<Gen>();
+    assert_sync::<Gen>();
+};
+
+/// A sink for the primitive draws of a `Gen`.
+type Tracer = Box<dyn FnMut(TraceEvent) + Send>;
+
+/// A primitive draw made by a `Gen`, as given to the sink set with
+/// `Gen::trace`.
+///
+/// Every `Arbitrary` impl in this crate generates values through a handful
+/// of primitive methods on `Gen`, so tracing them shows the decisions that
+/// led to a generated value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceEvent {
+    /// The primitive that was called: `size`, `choose`, `gen`, `gen_range`,
+    /// `gen_bool`, `next_u32`, `next_u64` or `fill_bytes`.
+    pub method: &'static str,
+    /// The parameters of the call, e.g., the range for `gen_range` or the
+    /// type for `gen`. This is empty for primitives without parameters.
+    pub params: String,
+    /// The value that was produced, formatted with `Debug`. For `choose`,
+    /// this is the index of the chosen element.
+    pub value: String,
+}
+
+/// Returns a seed drawn from the operating system's source of randomness.
+///
+/// If that fails, which is possible on platforms such as wasm, the seed is
+/// derived from the random keys of `HashMap`s and a counter instead, so
+/// that creating a `Gen` never panics. The keys may be constant on such
+/// platforms, in which case the seeds are different but predictable.
+fn entropy_seed() -> u64 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::Hasher;
+    use std::sync::atomic::{self, AtomicU64};
+
+    static FALLBACK_SEEDS: AtomicU64 = AtomicU64::new(0);
+
+    let mut seed = [0; 8];
+    match rand::rngs::OsRng.try_fill_bytes(&mut seed) {
+        Ok(()) => u64::from_le_bytes(seed),
+        Err(_) => {
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_u64(
+                FALLBACK_SEEDS.fetch_add(1, atomic::Ordering::Relaxed),
+            );
+            hasher.finish()
+        }
+    }
 }
 
 impl Gen {
@@ -46,38 +140,693 @@ impl Gen {
     /// vector, but is and should not be used to control the range of a
     /// randomly generated number. (Unless that number is used to control the
     /// size of a data structure.)
+    ///
+    /// Concretely, the `Arbitrary` implementations in this crate use `size`
+    /// as an exclusive upper bound on:
+    ///
+    /// * the length of a `Vec`, `VecDeque`, `LinkedList` or `BinaryHeap`,
+    /// * the number of entries generated for a `HashMap`, `HashSet`,
+    ///   `BTreeMap` or `BTreeSet` (duplicates may make the result smaller),
+    /// * the number of characters in a `String`, `OsString` or `CString`,
+    /// * the number of components appended to a `PathBuf`.
+    ///
+    /// Since the same `size` is passed to nested values, a `Vec<Vec<u8>>` may
+    /// hold up to `size * size` bytes in total. Numbers, `char`, `bool`,
+    /// network addresses, `Duration`, `SystemTime` and the other fixed size
+    /// types ignore `size`.
+    ///
+    /// When running properties with `QuickCheck`, the size defaults to `100`
+    /// and may be overridden with the `QUICKCHECK_GENERATOR_SIZE` environment
+    /// variable or `QuickCheck::with_generator_size`.
+    ///
+    /// The generator is seeded from a seed that is itself drawn from
+    /// entropy. It can be retrieved with `Gen::seed`.
     pub fn new(size: usize) -> Gen {
-        Gen { rng: rand::rngs::SmallRng::from_entropy(), size: size }
+        Gen::from_seed(size, entropy_seed())
+    }
+
+    /// Returns a `Gen` with the given size configuration whose random values
+    /// are entirely determined by `seed`.
+    ///
+    /// Two generators created with the same size and seed produce the same
+    /// sequence of values. The sequence for a particular seed is not
+    /// guaranteed to be stable across releases of this crate.
+    pub fn from_seed(size: usize, seed: u64) -> Gen {
+        Gen::from_rng(rand::rngs::SmallRng::seed_from_u64(seed), seed, size)
+    }
+
+    fn from_rng(rng: rand::rngs::SmallRng, seed: u64, size: usize) -> Gen {
+        Gen {
+            rng,
+            seed,
+            size,
+            #[cfg(debug_assertions)]
+            size_reads: AtomicUsize::new(0),
+            none_ratio: DEFAULT_NONE_RATIO,
+            err_ratio: DEFAULT_ERR_RATIO,
+            generators: HashMap::new(),
+            option_depth: 0,
+            option_contents: None,
+            tracer: None,
+        }
+    }
+
+    /// Returns a `Gen` seeded with `seed` that otherwise has the same
+    /// configuration as this one.
+    pub(crate) fn fork(&self, seed: u64) -> Gen {
+        Gen {
+            rng: rand::rngs::SmallRng::seed_from_u64(seed),
+            seed,
+            size: self.size,
+            #[cfg(debug_assertions)]
+            size_reads: AtomicUsize::new(0),
+            none_ratio: self.none_ratio,
+            err_ratio: self.err_ratio,
+            generators: self.generators.clone(),
+            option_depth: 0,
+            option_contents: None,
+            tracer: None,
+        }
+    }
+
+    /// Returns whether this generator hasn't drawn any random values since
+    /// it was created or reseeded.
+    pub(crate) fn is_fresh(&self) -> bool {
+        self.rng == rand::rngs::SmallRng::seed_from_u64(self.seed)
+    }
+
+    /// Seeds this generator with `seed`, as if it was created with it.
+    pub(crate) fn reseed(&mut self, seed: u64) {
+        self.rng = rand::rngs::SmallRng::seed_from_u64(seed);
+        self.seed = seed;
+    }
+
+    /// Returns the seed this generator was created with.
+    ///
+    /// Passing the seed to `Gen::from_seed` creates a generator that
+    /// produces the same values as this one did from the time it was
+    /// created.
+    pub fn seed(&self) -> u64 {
+        self.seed
     }
 
     /// Returns the size configured with this generator.
     pub fn size(&self) -> usize {
+        #[cfg(debug_assertions)]
+        self.size_reads.fetch_add(1, Ordering::Relaxed);
+        self.record("size", || (String::new(), format!("{:?}", self.size)));
         self.size
     }
 
+    /// Calls `sink` with every primitive draw made by this generator from
+    /// now on, replacing any sink set previously.
+    ///
+    /// This is meant for debugging `Arbitrary` impls that produce unexpected
+    /// values: the events show which sizes were consulted, which ranges were
+    /// drawn from and which choices were made. Generators that `QuickCheck`
+    /// derives from this one are not traced. Use `trace_arbitrary` to trace
+    /// the generation of a single value.
+    ///
+    /// When no sink is set, draws are not formatted, so tracing costs
+    /// nothing unless it is enabled.
+    pub fn trace<F>(&mut self, sink: F)
+    where
+        F: FnMut(TraceEvent) + Send + 'static,
+    {
+        self.tracer = Some(Mutex::new(Box::new(sink)));
+    }
+
+    /// Passes the draw described by `method` and `describe` to the sink set
+    /// with `trace`, if any. `describe` returns the parameters and the
+    /// value, and is only called when tracing.
+    pub(crate) fn record<F>(&self, method: &'static str, describe: F)
+    where
+        F: FnOnce() -> (String, String),
+    {
+        if let Some(ref tracer) = self.tracer {
+            let (params, value) = describe();
+            let mut tracer = tracer.lock().unwrap_or_else(|e| e.into_inner());
+            (tracer)(TraceEvent { method, params, value });
+        }
+    }
+
+    /// Returns the number of times `size` has been called on this generator
+    /// since it was created.
+    ///
+    /// This is only available in debug builds. It lets the tests of an
+    /// `Arbitrary` impl check the convention followed by the impls in this
+    /// crate: scalar values ignore the size, and only the lengths of
+    /// containers scale with it. For example, generating a `u32` leaves
+    /// the count unchanged, while generating a `Vec<u32>` increases it.
+    #[cfg(debug_assertions)]
+    pub fn size_reads(&self) -> usize {
+        self.size_reads.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_size(&mut self, size: usize) {
+        self.size = size;
+    }
+
+    /// Returns the probability with which `Option::arbitrary` generates
+    /// `None`.
+    ///
+    /// This defaults to `0.25`.
+    pub fn none_ratio(&self) -> f64 {
+        self.none_ratio
+    }
+
+    /// Sets the probability with which `Option::arbitrary` generates `None`.
+    ///
+    /// For example, a ratio of `0.0` makes every generated `Option` a `Some`,
+    /// and a ratio of `0.5` makes `None` and `Some` equally likely. The ratio
+    /// applies to every `Option` generated with this `Gen`, including those
+    /// nested inside other values, though it's divided by the nesting depth
+    /// of `Option`s; see `nested_none_ratio`.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `ratio` is not in the range `0.0..=1.0`.
+    pub fn set_none_ratio(&mut self, ratio: f64) {
+        assert!(
+            (0.0..=1.0).contains(&ratio),
+            "none ratio {} is not in the range 0.0..=1.0",
+            ratio
+        );
+        self.none_ratio = ratio;
+    }
+
+    /// Returns the number of `Option`s whose contents are being generated
+    /// with this `Gen`.
+    ///
+    /// This is `0` outside of `arbitrary_recursive_option`, and is one
+    /// greater for each nested call of it.
+    pub fn option_depth(&self) -> usize {
+        self.option_depth
+    }
+
+    /// Returns the probability with which `Option::arbitrary` generates
+    /// `None` at the current option depth.
+    ///
+    /// This is `none_ratio` divided by one more than `option_depth`, which
+    /// is capped at `3`. So outermost `Option`s are `None` with probability
+    /// `none_ratio`, and nested ones are less likely to be `None`. The cap
+    /// keeps recursive types such as linked lists of `Option`s from growing
+    /// without bound.
+    pub fn nested_none_ratio(&self) -> f64 {
+        self.none_ratio / (self.option_depth.min(3) + 1) as f64
+    }
+
+    /// Returns a guard that increases the option depth of this `Gen` by
+    /// one until it's dropped.
+    ///
+    /// The guard dereferences to the `Gen`, so values can be generated with
+    /// it in the meantime. This is what `arbitrary_recursive_option` uses,
+    /// and lets custom impls of option-like types share the same depth.
+    pub fn enter_option(&mut self) -> OptionDepthGuard<'_> {
+        let depth = self.option_depth + 1;
+        self.set_option_depth(depth)
+    }
+
+    /// Returns a guard that sets the option depth of this `Gen` to `depth`
+    /// until it's dropped. The contents of `Option`s generated in the
+    /// meantime aren't nested in an `Option` directly.
+    fn set_option_depth(&mut self, depth: usize) -> OptionDepthGuard<'_> {
+        let guard = OptionDepthGuard {
+            depth: self.option_depth,
+            contents: self.option_contents.take(),
+            g: self,
+        };
+        guard.g.option_depth = depth;
+        guard
+    }
+
+    /// Returns the probability with which `Result::arbitrary` generates
+    /// `Err`.
+    ///
+    /// This defaults to `0.25`.
+    pub fn err_ratio(&self) -> f64 {
+        self.err_ratio
+    }
+
+    /// Sets the probability with which `Result::arbitrary` generates `Err`.
+    ///
+    /// Like `set_none_ratio`, this applies to every `Result` generated with
+    /// this `Gen`.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `ratio` is not in the range `0.0..=1.0`.
+    pub fn set_err_ratio(&mut self, ratio: f64) {
+        assert!(
+            (0.0..=1.0).contains(&ratio),
+            "err ratio {} is not in the range 0.0..=1.0",
+            ratio
+        );
+        self.err_ratio = ratio;
+    }
+
     /// Choose among the possible alternatives in the slice given. If the slice
     /// is empty, then `None` is returned. Otherwise, a non-`None` value is
     /// guaranteed to be returned.
+    ///
+    /// This is the same as `try_choose`. Use `choose_or_panic` when the
+    /// slice is known not to be empty.
     pub fn choose<'a, T>(&mut self, slice: &'a [T]) -> Option<&'a T> {
-        slice.choose(&mut self.rng)
+        self.try_choose(slice)
+    }
+
+    /// Choose among the possible alternatives in the slice given, or return
+    /// `None` if the slice is empty.
+    pub fn try_choose<'a, T>(&mut self, slice: &'a [T]) -> Option<&'a T> {
+        let chosen = slice.choose(&mut self.rng);
+        self.record("choose", || {
+            let index = chosen
+                .and_then(|x| slice.iter().position(|y| std::ptr::eq(x, y)));
+            (format!("{} elements", slice.len()), format!("{:?}", index))
+        });
+        chosen
     }
 
-    fn gen<T>(&mut self) -> T
+    /// Choose among the possible alternatives in the slice given.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the slice is empty.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use quickcheck::Gen;
+    ///
+    /// let mut g = Gen::new(10);
+    /// let tld = g.choose_or_panic(&["com", "org", "net"]);
+    /// assert_eq!(tld.len(), 3);
+    /// ```
+    pub fn choose_or_panic<'a, T>(&mut self, slice: &'a [T]) -> &'a T {
+        match self.try_choose(slice) {
+            Some(x) => x,
+            None => panic!(
+                "cannot choose a {} from an empty slice",
+                type_name::<T>()
+            ),
+        }
+    }
+
+    /// Choose an index into a slice of length `len`, or return `None` if
+    /// `len` is zero.
+    ///
+    /// Every index is equally likely.
+    pub fn choose_index(&mut self, len: usize) -> Option<usize> {
+        let index =
+            if len == 0 { None } else { Some(self.rng.gen_range(0..len)) };
+        self.record("choose_index", || {
+            (format!("{}", len), format!("{:?}", index))
+        });
+        index
+    }
+
+    /// Choose `k` distinct elements of the slice given, in random order.
+    ///
+    /// Every subset of `k` elements is equally likely, and so is every
+    /// order of them. If `k` is greater than the length of the slice, then
+    /// all of its elements are returned (in random order).
+    pub fn choose_multiple<'a, T>(
+        &mut self,
+        slice: &'a [T],
+        k: usize,
+    ) -> Vec<&'a T> {
+        // A partial Fisher-Yates shuffle of the indices, where `moved` holds
+        // the indices that were swapped away from their position. This only
+        // takes `O(k)` time and memory, even for huge slices.
+        let (len, k) = (slice.len(), k.min(slice.len()));
+        let mut moved: HashMap<usize, usize> = HashMap::new();
+        let mut indices = Vec::with_capacity(k);
+        for i in 0..k {
+            let j = self.rng.gen_range(i..len);
+            let at_i = *moved.get(&i).unwrap_or(&i);
+            indices.push(*moved.get(&j).unwrap_or(&j));
+            moved.insert(j, at_i);
+        }
+        self.record("choose_multiple", || {
+            let params = format!("{} of {} elements", k, slice.len());
+            (params, format!("{:?}", indices))
+        });
+        indices.into_iter().map(|i| &slice[i]).collect()
+    }
+
+    /// Choose `k` elements of the slice given, each of which is chosen
+    /// independently and uniformly, so the same element may be chosen more
+    /// than once.
+    ///
+    /// If the slice is empty, then nothing can be chosen and the result is
+    /// empty.
+    pub fn choose_with_replacement<'a, T>(
+        &mut self,
+        slice: &'a [T],
+        k: usize,
+    ) -> Vec<&'a T> {
+        let indices: Vec<usize> = if slice.is_empty() {
+            vec![]
+        } else {
+            (0..k).map(|_| self.rng.gen_range(0..slice.len())).collect()
+        };
+        self.record("choose_with_replacement", || {
+            let params = format!("{} of {} elements", k, slice.len());
+            (params, format!("{:?}", indices))
+        });
+        indices.into_iter().map(|i| &slice[i]).collect()
+    }
+
+    /// Installs a generator for values of type `T`.
+    ///
+    /// Installed generators are used by `Registered<T>`, which makes it
+    /// possible to generate foreign types (that can't implement `Arbitrary`
+    /// because of the orphan rule) without defining a newtype for each one.
+    /// Installing a generator for a type replaces any generator previously
+    /// installed for it.
+    ///
+    /// Generators are kept by the `Gen`s that `QuickCheck` creates for each
+    /// test, so they may be installed on a `Gen` before passing it to
+    /// `QuickCheck::gen`.
+    pub fn install<T: 'static>(&mut self, generator: fn(&mut Gen) -> T) {
+        self.generators.insert(TypeId::of::<T>(), Arc::new(generator));
+    }
+
+    /// Generates a value of type `T` with the generator installed for it, or
+    /// returns `None` if no generator for `T` has been installed.
+    pub fn generate<T: 'static>(&mut self) -> Option<T> {
+        let generator = *self
+            .generators
+            .get(&TypeId::of::<T>())?
+            .downcast_ref::<fn(&mut Gen) -> T>()
+            .expect("generator is keyed by the type it generates");
+        Some(generator(self))
+    }
+
+    /// Generates values with `gen` until one satisfies `pred`, giving up
+    /// after `max_attempts` tries.
+    ///
+    /// Unlike retrying in a loop, this can't hang when `pred` is
+    /// unsatisfiable or rarely satisfied. Returns `None` if no generated
+    /// value satisfied `pred`. A property can then discard the test with
+    /// `TestResult::discard`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use quickcheck::{Arbitrary, Gen};
+    ///
+    /// let mut g = Gen::new(100);
+    /// let even = g.such_that(100, u32::arbitrary, |n| n % 2 == 0);
+    /// assert!(even.map_or(true, |n| n % 2 == 0));
+    /// ```
+    pub fn such_that<T, G, P>(
+        &mut self,
+        max_attempts: usize,
+        mut gen: G,
+        pred: P,
+    ) -> Option<T>
     where
+        G: FnMut(&mut Gen) -> T,
+        P: Fn(&T) -> bool,
+    {
+        (0..max_attempts).map(|_| gen(self)).find(|x| pred(x))
+    }
+
+    /// Returns a random `u32`.
+    ///
+    /// This and `next_u64` and `fill_bytes` give raw randomness, e.g., for
+    /// helpers ported from quickcheck 0.9 through `compat::Gen`.
+    pub fn next_u32(&mut self) -> u32 {
+        let x = self.rng.next_u32();
+        self.record("next_u32", || (String::new(), format!("{:?}", x)));
+        x
+    }
+
+    /// Returns a random `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        let x = self.rng.next_u64();
+        self.record("next_u64", || (String::new(), format!("{:?}", x)));
+        x
+    }
+
+    /// Fills `dest` with random bytes.
+    pub fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest);
+        self.record("fill_bytes", || {
+            (format!("{} bytes", dest.len()), format!("{:?}", dest))
+        });
+    }
+
+    pub(crate) fn gen<T>(&mut self) -> T
+    where
+        T: fmt::Debug,
         rand::distributions::Standard: rand::distributions::Distribution<T>,
     {
-        self.rng.gen()
+        let x = self.rng.gen();
+        self.record("gen", || {
+            (type_name::<T>().to_string(), format!("{:?}", x))
+        });
+        x
+    }
+
+    /// Returns `true` with probability `p`.
+    pub(crate) fn gen_bool(&mut self, p: f64) -> bool {
+        let x = self.rng.gen_bool(p);
+        self.record("gen_bool", || (format!("{:?}", p), format!("{:?}", x)));
+        x
     }
 
-    fn gen_range<T, R>(&mut self, range: R) -> T
+    /// Returns a random length in `0..size`, for deciding how many elements
+    /// a generated value holds. If `size` is `0`, then this returns `0`.
+    ///
+    /// This and `gen_edge_len` are the only ways that `Arbitrary` impls in
+    /// this crate consult the size, which keeps it governing counts rather
+    /// than magnitudes.
+    pub(crate) fn gen_len(&mut self) -> usize {
+        let size = self.size();
+        if size == 0 {
+            0
+        } else {
+            self.gen_value_range(0..size)
+        }
+    }
+
+    /// Returns a random length in `0..=size` that favors the edges: `0` and
+    /// `size` are each returned 10% of the time, and otherwise the length
+    /// is uniform in `1..size`.
+    pub(crate) fn gen_edge_len(&mut self) -> usize {
+        let size = self.size();
+        match self.gen_value_range(0..10) {
+            0 => 0,
+            1 => size,
+            _ if size > 1 => self.gen_value_range(1..size),
+            _ => 0,
+        }
+    }
+
+    /// Returns a random value in `range`. Unlike `gen_len`, the range never
+    /// depends on the size.
+    pub(crate) fn gen_value_range<T, R>(&mut self, range: R) -> T
     where
-        T: rand::distributions::uniform::SampleUniform,
-        R: rand::distributions::uniform::SampleRange<T>,
+        T: rand::distributions::uniform::SampleUniform + fmt::Debug,
+        R: rand::distributions::uniform::SampleRange<T> + fmt::Debug,
     {
-        self.rng.gen_range(range)
+        let params = match self.tracer {
+            None => String::new(),
+            Some(_) => format!("{:?}", range),
+        };
+        let x = self.rng.gen_range(range);
+        self.record("gen_range", || (params, format!("{:?}", x)));
+        x
+    }
+}
+
+/// A value generated by the generator installed for `T` with `Gen::install`.
+///
+/// This permits using types from other crates as property arguments without
+/// wrapping each one in a newtype that implements `Arbitrary`. Values of this
+/// type are not shrunk.
+///
+/// # Panics
+///
+/// Generating a `Registered<T>` panics if no generator for `T` has been
+/// installed.
+///
+/// # Example
+///
+/// ```rust
+/// use quickcheck::{Gen, QuickCheck, Registered};
+///
+/// # #[derive(Clone, Debug)] struct Uuid(u128);
+/// fn uuid(g: &mut Gen) -> Uuid {
+///     Uuid(quickcheck::Arbitrary::arbitrary(g))
+/// }
+///
+/// fn prop(id: Registered<Uuid>) -> bool {
+///     let Registered(Uuid(_)) = id;
+///     true
+/// }
+///
+/// let mut gen = Gen::new(100);
+/// gen.install(uuid);
+/// QuickCheck::new()
+///     .gen(gen)
+///     .quickcheck(prop as fn(Registered<Uuid>) -> bool);
+/// ```
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Registered<T>(pub T);
+
+impl<T: Clone + 'static> Arbitrary for Registered<T> {
+    fn arbitrary(g: &mut Gen) -> Registered<T> {
+        match g.generate() {
+            Some(x) => Registered(x),
+            None => panic!(
+                "no generator installed for type {}; \
+                 register one with Gen::install",
+                type_name::<T>()
+            ),
+        }
+    }
+}
+
+/// A shared value generated by the generator installed for `T` with
+/// `Gen::install`.
+///
+/// `Arbitrary` requires `Clone`, which types owning a unique resource (a file
+/// handle, a connection guard, a boxed trait object) usually can't
+/// implement. This wrapper generates such a `T` and keeps it behind an `Arc`,
+/// so that the wrapper itself is `Clone`. It dereferences to `T`, and formats
+/// as `T` does with `Debug`. Values of this type are not shrunk.
+///
+/// # Panics
+///
+/// Generating an `ArcArbitrary<T>` panics if no generator for `T` has been
+/// installed.
+///
+/// # Example
+///
+/// ```rust
+/// use quickcheck::{Arbitrary, ArcArbitrary, Gen, QuickCheck};
+///
+/// #[derive(Debug)]
+/// struct Buffer {
+///     bytes: Vec<u8>,
+/// }
+///
+/// fn buffer(g: &mut Gen) -> Buffer {
+///     Buffer { bytes: Vec::arbitrary(g) }
+/// }
+///
+/// fn prop(buf: ArcArbitrary<Buffer>) -> bool {
+///     buf.bytes.len() < 1_000
+/// }
+///
+/// let mut gen = Gen::new(100);
+/// gen.install(buffer);
+/// QuickCheck::new()
+///     .gen(gen)
+///     .quickcheck(prop as fn(ArcArbitrary<Buffer>) -> bool);
+/// ```
+pub struct ArcArbitrary<T>(pub Arc<T>);
+
+impl<T> Clone for ArcArbitrary<T> {
+    fn clone(&self) -> ArcArbitrary<T> {
+        ArcArbitrary(self.0.clone())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArcArbitrary<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
     }
 }
 
+impl<T> Deref for ArcArbitrary<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: 'static> Arbitrary for ArcArbitrary<T> {
+    fn arbitrary(g: &mut Gen) -> ArcArbitrary<T> {
+        match g.generate() {
+            Some(x) => ArcArbitrary(Arc::new(x)),
+            None => panic!(
+                "no generator installed for type {}; \
+                 register one with Gen::install",
+                type_name::<T>()
+            ),
+        }
+    }
+}
+
+/// Returns whether `T` is `Infallible`, which has no values to generate.
+fn is_infallible<T: 'static>() -> bool {
+    TypeId::of::<T>() == TypeId::of::<Infallible>()
+}
+
+/// Generates an arbitrary value of type `T`.
+///
+/// This is the same as `T::arbitrary(g)`, but lets the type be inferred,
+/// which is convenient in properties that take a `Gen` directly.
+///
+/// # Example
+///
+/// ```rust
+/// use quickcheck::{any, Gen, QuickCheck, TestResult};
+///
+/// QuickCheck::new().quickcheck(|g: &mut Gen| {
+///     let xs: Vec<u8> = any(g);
+///     let i: usize = any(g);
+///     TestResult::from_bool(xs.is_empty() || xs.contains(&xs[i % xs.len()]))
+/// });
+/// ```
+pub fn any<T: Arbitrary>(g: &mut Gen) -> T {
+    T::arbitrary(g)
+}
+
+/// Generates arbitrary values of type `A` until one satisfies `pred`,
+/// giving up after `max_attempts` tries.
+///
+/// This is `Gen::such_that` with `A::arbitrary` as the generator.
+pub fn arbitrary_such_that<A, P>(
+    g: &mut Gen,
+    max_attempts: usize,
+    pred: P,
+) -> Option<A>
+where
+    A: Arbitrary,
+    P: Fn(&A) -> bool,
+{
+    g.such_that(max_attempts, A::arbitrary, pred)
+}
+
+/// Generates a value of type `A` with a new `Gen` of the given size, and
+/// returns it along with every primitive draw made to generate it.
+///
+/// This is a convenient way to see why an `Arbitrary` impl produces the
+/// values that it does. See `Gen::trace` for tracing an existing `Gen`.
+///
+/// # Example
+///
+/// ```rust
+/// let (xs, events) = quickcheck::trace_arbitrary::<Vec<bool>>(10);
+/// assert_eq!(events[0].method, "size");
+/// assert_eq!(events.len(), 2 + xs.len());
+/// ```
+pub fn trace_arbitrary<A: Arbitrary>(size: usize) -> (A, Vec<TraceEvent>) {
+    let events = Arc::new(Mutex::new(vec![]));
+    let mut g = Gen::new(size);
+    let sink = Arc::clone(&events);
+    g.trace(move |event| sink.lock().unwrap().push(event));
+    let x = A::arbitrary(&mut g);
+    let events = mem::take(&mut *events.lock().unwrap());
+    (x, events)
+}
+
 /// Creates a shrinker with zero elements.
 pub fn empty_shrinker<A: 'static>() -> Box<dyn Iterator<Item = A>> {
     Box::new(empty())
@@ -88,6 +837,32 @@ pub fn single_shrinker<A: 'static>(value: A) -> Box<dyn Iterator<Item = A>> {
     Box::new(once(value))
 }
 
+/// Generates an arbitrary value and returns its negation.
+///
+/// This is a convenience for properties about `Neg`, such as
+/// `-(-x) == x`. Operators can also be applied inline, e.g.,
+/// `!u8::arbitrary(g)`.
+///
+/// Negation happens with `T`'s own `Neg` impl, so this panics wherever `-x`
+/// would. In particular, with overflow checks enabled, negating the minimum
+/// value of a primitive signed integer panics. Use `Wrapping<T>` to get
+/// wrapping negation instead.
+///
+/// # Example
+///
+/// ```rust
+/// use std::num::Wrapping;
+///
+/// use quickcheck::{negate, Gen};
+///
+/// let mut g = Gen::new(100);
+/// let x: Wrapping<i8> = negate(&mut g);
+/// assert_eq!(-(-x), x);
+/// ```
+pub fn negate<T: Arbitrary + Neg<Output = T>>(g: &mut Gen) -> T {
+    -T::arbitrary(g)
+}
+
 /// `Arbitrary` describes types whose values can be randomly generated and
 /// shrunk.
 ///
@@ -128,6 +903,81 @@ pub trait Arbitrary: Clone + 'static {
     fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
         empty_shrinker()
     }
+
+    /// Return an iterator of values that are smaller than itself, given
+    /// information about why the property failed on this value.
+    ///
+    /// When shrinking the arguments of a failing property, QuickCheck calls
+    /// this method rather than `shrink`. Implementations can use the failure
+    /// message to try likely candidates first or to skip candidates that
+    /// can't fail in the same way. For example, a shrinker may jump straight
+    /// to a threshold mentioned in an assertion message.
+    ///
+    /// The default implementation ignores the context and calls `shrink`.
+    /// Tuples pass the context on to each of their elements.
+    fn shrink_with_context(
+        &self,
+        _ctx: &FailureContext,
+    ) -> Box<dyn Iterator<Item = Self>> {
+        self.shrink()
+    }
+}
+
+/// Describes the distribution of the values generated by an `Arbitrary`
+/// impl.
+///
+/// Many impls generate special cases, such as the problem values of
+/// integers or `None`, with a fixed probability, and generate other values
+/// in ways that either do or don't depend on `Gen::size`. This makes those
+/// contracts visible to tooling, e.g., to estimate how many tests are
+/// needed to see a special case. The impls in this crate are checked
+/// against the frequencies observed in samples, so they change whenever the
+/// strategies do.
+///
+/// The defaults are conservative: no special cases and no dependence on
+/// the size.
+pub trait DistributionInfo: Arbitrary {
+    /// Returns the probability that `arbitrary` takes the branch that
+    /// generates its special cases, with a `Gen` whose ratios are the
+    /// defaults.
+    ///
+    /// What the special cases are depends on the type: e.g., the problem
+    /// values of numbers, `None` for `Option`, `Err` for `Result` and the
+    /// empty and longest strings for `String`. Other branches may generate
+    /// a special case by chance, which is common for narrow types: e.g.,
+    /// `u8` generates one of its problem values somewhat more often than
+    /// this.
+    fn special_case_probability() -> f64 {
+        0.0
+    }
+
+    /// Returns whether `arbitrary` consults `Gen::size`, e.g., to choose the
+    /// length of a collection.
+    fn respects_size() -> bool {
+        false
+    }
+}
+
+/// Information about a failure of a property, given to
+/// `Arbitrary::shrink_with_context`.
+#[derive(Clone, Debug, Default)]
+pub struct FailureContext {
+    message: Option<String>,
+}
+
+impl FailureContext {
+    /// Create a context for a failure with the given error or panic message.
+    pub fn new(message: Option<String>) -> FailureContext {
+        FailureContext { message }
+    }
+
+    /// Returns the error or panic message of the most recent failing call of
+    /// the property, as it would be reported.
+    ///
+    /// Properties that fail by returning `false` have no message.
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
 }
 
 impl Arbitrary for () {
@@ -150,13 +1000,97 @@ impl Arbitrary for bool {
     }
 }
 
+/// Increases the option depth of a `Gen` until it's dropped.
+///
+/// This is created by `Gen::enter_option`, and dereferences to the `Gen`.
+pub struct OptionDepthGuard<'a> {
+    g: &'a mut Gen,
+    /// The option depth and contents to restore.
+    depth: usize,
+    contents: Option<TypeId>,
+}
+
+impl Deref for OptionDepthGuard<'_> {
+    type Target = Gen;
+
+    fn deref(&self) -> &Gen {
+        self.g
+    }
+}
+
+impl DerefMut for OptionDepthGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Gen {
+        self.g
+    }
+}
+
+impl Drop for OptionDepthGuard<'_> {
+    fn drop(&mut self) {
+        self.g.option_depth = self.depth;
+        self.g.option_contents = self.contents;
+    }
+}
+
+/// Generates `None` with probability `g.nested_none_ratio()`, and otherwise
+/// generates the contents of a `Some` with `gen` at one more option depth.
+///
+/// This is how `Option::arbitrary` is implemented. Since `None` gets less
+/// likely the deeper `Option`s are nested, deeply nested types such as
+/// `Option<Option<Option<T>>>` still generate their innermost values
+/// reasonably often. Custom impls of option-like recursive types can use it
+/// to get the same bias.
+///
+/// # Example
+///
+/// ```rust
+/// use quickcheck::{arbitrary_recursive_option, Arbitrary, Gen};
+///
+/// #[derive(Clone, Debug)]
+/// struct Chain(Option<Box<Chain>>);
+///
+/// impl Arbitrary for Chain {
+///     fn arbitrary(g: &mut Gen) -> Chain {
+///         let next = arbitrary_recursive_option(g, Chain::arbitrary);
+///         Chain(next.map(Box::new))
+///     }
+/// }
+///
+/// let chain = Chain::arbitrary(&mut Gen::new(10));
+/// ```
+pub fn arbitrary_recursive_option<A, F>(g: &mut Gen, gen: F) -> Option<A>
+where
+    F: FnOnce(&mut Gen) -> A,
+{
+    let none_ratio = g.nested_none_ratio();
+    if g.gen_bool(none_ratio) {
+        None
+    } else {
+        Some(gen(&mut g.enter_option()))
+    }
+}
+
+/// Generates `None` one time in four by default. The ratio can be changed
+/// with `Gen::set_none_ratio`. `Option`s nested directly in other `Option`s,
+/// such as the inner ones of `Option<Option<T>>`, are less likely to be
+/// `None`; see `arbitrary_recursive_option`. `Option`s inside other values,
+/// such as the elements of `Option<Vec<Option<T>>>`, are not nested.
+///
+/// The same ratio is used for every `A`, including types whose `Option` is
+/// represented with a niche, such as `Option<NonZeroU8>`. (A dedicated impl
+/// for those types would overlap with this one.) To generate them as if from
+/// their raw representation, where `None` is just one of the 256 possible
+/// bytes, set the ratio to `1.0 / 256.0`.
+///
+/// Shrinking tries `None` first, followed by the shrunk values of the
+/// contents of a `Some`.
 impl<A: Arbitrary> Arbitrary for Option<A> {
     fn arbitrary(g: &mut Gen) -> Option<A> {
-        if g.gen() {
-            None
-        } else {
-            Some(Arbitrary::arbitrary(g))
-        }
+        let nested = g.option_contents == Some(TypeId::of::<Option<A>>());
+        let depth = if nested { g.option_depth } else { 0 };
+        arbitrary_recursive_option(&mut g.set_option_depth(depth), |g| {
+            g.option_contents = Some(TypeId::of::<A>());
+            A::arbitrary(g)
+        })
     }
 
     fn shrink(&self) -> Box<dyn Iterator<Item = Option<A>>> {
@@ -170,12 +1104,25 @@ impl<A: Arbitrary> Arbitrary for Option<A> {
     }
 }
 
+/// Generates `Err` one time in four by default. The ratio can be changed
+/// with `Gen::set_err_ratio`. A variant holding `Infallible` is never
+/// generated, so `Result<T, Infallible>` is always `Ok`.
+///
+/// Shrinking shrinks the contents of the `Ok` or `Err`, keeping the variant.
 impl<A: Arbitrary, B: Arbitrary> Arbitrary for Result<A, B> {
     fn arbitrary(g: &mut Gen) -> Result<A, B> {
-        if g.gen() {
-            Ok(Arbitrary::arbitrary(g))
+        let err = if is_infallible::<B>() {
+            false
+        } else if is_infallible::<A>() {
+            true
         } else {
+            let err_ratio = g.err_ratio;
+            g.gen_bool(err_ratio)
+        };
+        if err {
             Err(Arbitrary::arbitrary(g))
+        } else {
+            Ok(Arbitrary::arbitrary(g))
         }
     }
 
@@ -195,6 +1142,36 @@ impl<A: Arbitrary, B: Arbitrary> Arbitrary for Result<A, B> {
     }
 }
 
+/// Yields items from each of `iters` in turn, dropping iterators as they are
+/// exhausted.
+///
+/// Tuples use this to shrink all of their positions at an even pace, rather
+/// than trying every shrunk value of the first position before the second.
+struct Interleave<T> {
+    iters: Vec<Box<dyn Iterator<Item = T>>>,
+    next: usize,
+}
+
+impl<T> Iterator for Interleave<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        while !self.iters.is_empty() {
+            if self.next >= self.iters.len() {
+                self.next = 0;
+            }
+            match self.iters[self.next].next() {
+                Some(item) => {
+                    self.next += 1;
+                    return Some(item);
+                }
+                None => drop(self.iters.remove(self.next)),
+            }
+        }
+        None
+    }
+}
+
 macro_rules! impl_arb_for_single_tuple {
     ($(($type_param:ident, $tuple_index:tt),)*) => {
         impl<$($type_param),*> Arbitrary for ($($type_param,)*)
@@ -209,23 +1186,82 @@ macro_rules! impl_arb_for_single_tuple {
             }
 
             fn shrink(&self) -> Box<dyn Iterator<Item=($($type_param,)*)>> {
-                let iter = ::std::iter::empty();
-                $(
-                    let cloned = self.clone();
-                    let iter = iter.chain(
-                        self.$tuple_index.shrink().map(move |shr_value| {
-                            let mut result = cloned.clone();
-                            result.$tuple_index = shr_value;
-                            result
-                        })
-                    );
-                )*
-                Box::new(iter)
+                let mut iters: Vec<
+                    Box<dyn Iterator<Item=($($type_param,)*)>>,
+                > = vec![];
+                tuple_shrinkers!(
+                    self, iters, shrink(),
+                    [] [$(($type_param, $tuple_index),)*]
+                );
+                Box::new(Interleave { iters, next: 0 })
+            }
+
+            fn shrink_with_context(
+                &self,
+                ctx: &FailureContext,
+            ) -> Box<dyn Iterator<Item=($($type_param,)*)>> {
+                let mut iters: Vec<
+                    Box<dyn Iterator<Item=($($type_param,)*)>>,
+                > = vec![];
+                tuple_shrinkers!(
+                    self, iters, shrink_with_context(ctx),
+                    [] [$(($type_param, $tuple_index),)*]
+                );
+                Box::new(Interleave { iters, next: 0 })
+            }
+        }
+
+        impl<$($type_param),*> DistributionInfo for ($($type_param,)*)
+            where $($type_param: DistributionInfo,)*
+        {
+            fn respects_size() -> bool {
+                false $(|| $type_param::respects_size())*
             }
         }
     };
 }
 
+/// Pushes a shrinker for each element of the tuple `$this` onto `$iters`,
+/// calling `$method` on each element to shrink it.
+///
+/// Each shrinker only clones the *other* elements of the tuple, so that a
+/// large element is never cloned just to be replaced by its shrunk value.
+macro_rules! tuple_shrinkers {
+    (
+        $this:ident,
+        $iters:ident,
+        $method:ident($($arg:ident),*),
+        [$(($bty:ident, $b:tt),)*]
+        []
+    ) => {};
+    (
+        $this:ident,
+        $iters:ident,
+        $method:ident($($arg:ident),*),
+        [$(($bty:ident, $b:tt),)*]
+        [($ty:ident, $i:tt), $(($aty:ident, $a:tt),)*]
+    ) => {
+        let others = (
+            $(Some($this.$b.clone()),)*
+            None::<$ty>,
+            $(Some($this.$a.clone()),)*
+        );
+        let shrunk = $this.$i.$method($($arg),*);
+        $iters.push(Box::new(shrunk.map(move |shr_value| {
+            let mut parts = others.clone();
+            parts.$i = Some(shr_value);
+            ($(parts.$b.unwrap(),)* parts.$i.unwrap(), $(parts.$a.unwrap(),)*)
+        })));
+        tuple_shrinkers!(
+            $this,
+            $iters,
+            $method($($arg),*),
+            [$(($bty, $b),)* ($ty, $i),]
+            [$(($aty, $a),)*]
+        );
+    };
+}
+
 macro_rules! impl_arb_for_tuples {
     (@internal [$($acc:tt,)*]) => { };
     (@internal [$($acc:tt,)*] ($type_param:ident, $tuple_index:tt), $($rest:tt,)*) => {
@@ -248,12 +1284,58 @@ impl_arb_for_tuples! {
     (H, 7),
 }
 
+/// Each element is generated independently. Shrinking shrinks one element
+/// at a time, from first to last, since the length of an array is fixed.
+///
+/// # Example
+///
+/// Arrays of any length can be generated, such as the common sizes of UUIDs,
+/// SHA-256 digests and SHA-512 digests:
+///
+/// ```rust
+/// use quickcheck::{any, Gen};
+///
+/// let mut g = Gen::new(100);
+/// let uuid: [u8; 16] = any(&mut g);
+/// assert_ne!(uuid, any::<[u8; 16]>(&mut g));
+/// ```
+///
+/// ```rust
+/// use quickcheck::{any, Gen};
+///
+/// let mut g = Gen::new(100);
+/// let sha256: [u8; 32] = any(&mut g);
+/// assert_ne!(sha256, any::<[u8; 32]>(&mut g));
+/// ```
+///
+/// ```rust
+/// use quickcheck::{any, Gen};
+///
+/// let mut g = Gen::new(100);
+/// let sha512: [u8; 64] = any(&mut g);
+/// assert_ne!(sha512, any::<[u8; 64]>(&mut g));
+/// ```
+impl<A: Arbitrary, const N: usize> Arbitrary for [A; N] {
+    fn arbitrary(g: &mut Gen) -> [A; N] {
+        std::array::from_fn(|_| A::arbitrary(g))
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = [A; N]>> {
+        let xs = self.clone();
+        Box::new((0..N).flat_map(move |i| {
+            let xs = xs.clone();
+            xs[i].shrink().map(move |x| {
+                let mut shrunk = xs.clone();
+                shrunk[i] = x;
+                shrunk
+            })
+        }))
+    }
+}
+
 impl<A: Arbitrary> Arbitrary for Vec<A> {
     fn arbitrary(g: &mut Gen) -> Vec<A> {
-        let size = {
-            let s = g.size();
-            g.gen_range(0..s)
-        };
+        let size = g.gen_len();
         (0..size).map(|_| A::arbitrary(g)).collect()
     }
 
@@ -388,8 +1470,15 @@ impl<
     }
 
     fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        // Candidates keep the hasher of the original, since the behavior of
+        // the map (e.g., its iteration order) may depend on its state.
+        let hasher = self.hasher().clone();
         let vec: Vec<(K, V)> = self.clone().into_iter().collect();
-        Box::new(vec.shrink().map(|v| v.into_iter().collect::<Self>()))
+        Box::new(vec.shrink().map(move |v| {
+            let mut map = HashMap::with_hasher(hasher.clone());
+            map.extend(v);
+            map
+        }))
     }
 }
 
@@ -400,8 +1489,71 @@ impl<T: Arbitrary + Ord> Arbitrary for BTreeSet<T> {
     }
 
     fn shrink(&self) -> Box<dyn Iterator<Item = BTreeSet<T>>> {
-        let vec: Vec<T> = self.clone().into_iter().collect();
-        Box::new(vec.shrink().map(|v| v.into_iter().collect::<BTreeSet<T>>()))
+        Box::new(BTreeSetShrinker::new(self.clone()))
+    }
+}
+
+/// Iterator which returns successive attempts to shrink the set `seed`.
+///
+/// It tries the empty set, then removing each element, and then shrinking
+/// each element, all in ascending order of the elements. An element is
+/// only shrunk to values that aren't in the set, so no set is tried twice.
+struct BTreeSetShrinker<T> {
+    seed: BTreeSet<T>,
+    /// The elements of `seed`, in ascending order
+    elements: Vec<T>,
+    /// Whether the empty set is yet to be tried
+    empty: bool,
+    /// The index of the next element to remove
+    removed: usize,
+    /// The index of the element after the one `element_shrinker` shrinks
+    shrunk: usize,
+    element_shrinker: Box<dyn Iterator<Item = T>>,
+}
+
+impl<T: Arbitrary + Ord> BTreeSetShrinker<T> {
+    fn new(seed: BTreeSet<T>) -> BTreeSetShrinker<T> {
+        BTreeSetShrinker {
+            elements: seed.iter().cloned().collect(),
+            empty: seed.len() > 1,
+            seed,
+            removed: 0,
+            shrunk: 0,
+            element_shrinker: empty_shrinker(),
+        }
+    }
+}
+
+impl<T: Arbitrary + Ord> Iterator for BTreeSetShrinker<T> {
+    type Item = BTreeSet<T>;
+
+    fn next(&mut self) -> Option<BTreeSet<T>> {
+        if self.empty {
+            self.empty = false;
+            return Some(BTreeSet::new());
+        }
+        if let Some(x) = self.elements.get(self.removed) {
+            self.removed += 1;
+            let mut set = self.seed.clone();
+            set.remove(x);
+            return Some(set);
+        }
+        loop {
+            match self.element_shrinker.next() {
+                Some(y) if self.seed.contains(&y) => {}
+                Some(y) => {
+                    let mut set = self.seed.clone();
+                    set.remove(&self.elements[self.shrunk - 1]);
+                    set.insert(y);
+                    return Some(set);
+                }
+                None => {
+                    let x = self.elements.get(self.shrunk)?;
+                    self.element_shrinker = x.shrink();
+                    self.shrunk += 1;
+                }
+            }
+        }
     }
 }
 
@@ -428,8 +1580,14 @@ impl<T: Arbitrary + Eq + Hash, S: BuildHasher + Default + Clone + 'static>
     }
 
     fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        // See the `HashMap` impl.
+        let hasher = self.hasher().clone();
         let vec: Vec<T> = self.clone().into_iter().collect();
-        Box::new(vec.shrink().map(|v| v.into_iter().collect::<Self>()))
+        Box::new(vec.shrink().map(move |v| {
+            let mut set = HashSet::with_hasher(hasher.clone());
+            set.extend(v);
+            set
+        }))
     }
 }
 
@@ -440,9 +1598,12 @@ impl<T: Arbitrary> Arbitrary for LinkedList<T> {
     }
 
     fn shrink(&self) -> Box<dyn Iterator<Item = LinkedList<T>>> {
-        let vec: Vec<T> = self.clone().into_iter().collect();
+        // Shrink a single copy of the list as a `Vec`, rather than going
+        // through `Vec::shrink`, which would clone it again.
+        let vec: Vec<T> = self.iter().cloned().collect();
         Box::new(
-            vec.shrink().map(|v| v.into_iter().collect::<LinkedList<T>>()),
+            VecShrinker::new(vec)
+                .map(|v| v.into_iter().collect::<LinkedList<T>>()),
         )
     }
 }
@@ -470,24 +1631,68 @@ impl Arbitrary for IpAddr {
     }
 }
 
+/// IPv4 addresses with special meaning, which networking code often
+/// handles differently.
+const IPV4_PROBLEM_VALUES: &[Ipv4Addr] = &[
+    Ipv4Addr::UNSPECIFIED,
+    Ipv4Addr::LOCALHOST,
+    Ipv4Addr::BROADCAST,
+    // Private.
+    Ipv4Addr::new(10, 0, 0, 1),
+    Ipv4Addr::new(172, 16, 0, 1),
+    Ipv4Addr::new(172, 31, 255, 255),
+    Ipv4Addr::new(192, 168, 0, 1),
+    // Link-local.
+    Ipv4Addr::new(169, 254, 0, 1),
+    // Multicast.
+    Ipv4Addr::new(224, 0, 0, 1),
+    Ipv4Addr::new(239, 255, 255, 255),
+];
+
 impl Arbitrary for Ipv4Addr {
     fn arbitrary(g: &mut Gen) -> Ipv4Addr {
-        Ipv4Addr::new(g.gen(), g.gen(), g.gen(), g.gen())
+        match g.gen_value_range(0..10) {
+            0 => *g.choose(IPV4_PROBLEM_VALUES).unwrap(),
+            _ => Ipv4Addr::new(g.gen(), g.gen(), g.gen(), g.gen()),
+        }
     }
 }
 
+/// IPv6 addresses with special meaning, which networking code often
+/// handles differently.
+const IPV6_PROBLEM_VALUES: &[Ipv6Addr] = &[
+    Ipv6Addr::UNSPECIFIED,
+    Ipv6Addr::LOCALHOST,
+    // Link-local.
+    Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 0),
+    Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1),
+    // Multicast to all nodes and all routers on the link.
+    Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 1),
+    Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 2),
+    // IPv4-mapped `127.0.0.1`.
+    Ipv6Addr::new(0, 0, 0, 0, 0, 0xffff, 0x7f00, 1),
+    // Documentation.
+    Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1),
+    Ipv6Addr::new(
+        0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff,
+    ),
+];
+
 impl Arbitrary for Ipv6Addr {
     fn arbitrary(g: &mut Gen) -> Ipv6Addr {
-        Ipv6Addr::new(
-            g.gen(),
-            g.gen(),
-            g.gen(),
-            g.gen(),
-            g.gen(),
-            g.gen(),
-            g.gen(),
-            g.gen(),
-        )
+        match g.gen_value_range(0..10) {
+            0 => *g.choose(IPV6_PROBLEM_VALUES).unwrap(),
+            _ => Ipv6Addr::new(
+                g.gen(),
+                g.gen(),
+                g.gen(),
+                g.gen(),
+                g.gen(),
+                g.gen(),
+                g.gen(),
+                g.gen(),
+            ),
+        }
     }
 }
 
@@ -505,19 +1710,84 @@ impl Arbitrary for SocketAddrV4 {
 
 impl Arbitrary for SocketAddrV6 {
     fn arbitrary(g: &mut Gen) -> SocketAddrV6 {
-        SocketAddrV6::new(Arbitrary::arbitrary(g), g.gen(), g.gen(), g.gen())
+        let (ip, port) = (Arbitrary::arbitrary(g), g.gen());
+        // The flow label only has 20 bits.
+        let flowinfo = g.gen::<u32>() & 0xF_FFFF;
+        // Most addresses have the global scope, or the scope of one of the
+        // first few interfaces.
+        let scope_id = match g.gen_value_range(0..4) {
+            0 => 0,
+            1 => 1,
+            _ => g.gen(),
+        };
+        SocketAddrV6::new(ip, port, flowinfo, scope_id)
+    }
+}
+
+/// Path components that file system code often gets wrong.
+const PATH_PROBLEM_COMPONENTS: &[&str] = &[
+    " ",
+    "a b",
+    " leading",
+    "trailing ",
+    ".hidden",
+    "...",
+    "trailing.",
+    "archive.tar.gz",
+    "-dash",
+    "ünïcödé",
+    "日本語",
+    "\u{1F600}",
+];
+
+/// Returns the current, temporary and home directories, which are used as
+/// the roots of generated paths.
+///
+/// Real directories are used as guesses, so we may end up with actual
+/// working directories in case that is relevant.
+#[cfg(not(target_arch = "wasm32"))]
+fn path_roots() -> [PathBuf; 3] {
+    let here =
+        std::env::current_dir().unwrap_or(PathBuf::from("/test/directory"));
+    let temp = std::env::temp_dir();
+    #[allow(deprecated)]
+    let home = std::env::home_dir().unwrap_or(PathBuf::from("/home/user"));
+    [here, temp, home]
+}
+
+/// On wasm, there is usually no file system to probe, and `env::temp_dir`
+/// panics, so only made up directories are used.
+#[cfg(target_arch = "wasm32")]
+fn path_roots() -> [PathBuf; 3] {
+    [
+        PathBuf::from("/test/directory"),
+        PathBuf::from("/tmp"),
+        PathBuf::from("/home/user"),
+    ]
+}
+
+/// Returns a single arbitrary path component.
+fn arbitrary_path_component(g: &mut Gen) -> OsString {
+    match g.gen_value_range(0..8) {
+        0 => OsString::from(*g.choose(PATH_PROBLEM_COMPONENTS).unwrap()),
+        // Names at and just past the usual limit of 255 bytes.
+        1 => OsString::from("a".repeat(g.gen_value_range(255..=256))),
+        #[cfg(unix)]
+        2 => {
+            use std::os::unix::ffi::OsStringExt;
+
+            // Bytes that are never valid UTF-8 on their own.
+            let len = g.gen_len() + 1;
+            let bytes = (0..len).map(|_| g.gen_value_range(0x80..=0xFF));
+            OsString::from_vec(bytes.collect())
+        }
+        _ => OsString::arbitrary(g),
     }
 }
 
 impl Arbitrary for PathBuf {
     fn arbitrary(g: &mut Gen) -> PathBuf {
-        // use some real directories as guesses, so we may end up with
-        // actual working directories in case that is relevant.
-        let here =
-            env::current_dir().unwrap_or(PathBuf::from("/test/directory"));
-        let temp = env::temp_dir();
-        #[allow(deprecated)]
-        let home = env::home_dir().unwrap_or(PathBuf::from("/home/user"));
+        let [here, temp, home] = path_roots();
         let mut p = g
             .choose(&[
                 here,
@@ -530,30 +1800,54 @@ impl Arbitrary for PathBuf {
             ])
             .unwrap()
             .to_owned();
-        p.extend(Vec::<OsString>::arbitrary(g).iter());
+        let len = g.gen_len();
+        p.extend((0..len).map(|_| arbitrary_path_component(g)));
+        match g.gen_value_range(0..10) {
+            // A trailing separator.
+            0 => p.push(""),
+            // A doubled separator.
+            1 => {
+                let mut s = p.into_os_string();
+                s.push(format!("{0}{0}", MAIN_SEPARATOR));
+                s.push(arbitrary_path_component(g));
+                p = PathBuf::from(s);
+            }
+            _ => {}
+        }
         p
     }
 
     fn shrink(&self) -> Box<dyn Iterator<Item = PathBuf>> {
         let mut shrunk = vec![];
+        let mut push = |p: PathBuf| {
+            if p.as_os_str() != self.as_os_str() && !shrunk.contains(&p) {
+                shrunk.push(p);
+            }
+        };
+        if !self.as_os_str().is_empty() {
+            push(PathBuf::new());
+        }
+        // Parts of the file name on their own are often enough to trigger
+        // the same bug, and are much shorter than the full path.
+        let parts = [self.file_stem(), self.extension(), self.file_name()];
+        for part in parts.iter().flatten() {
+            push(PathBuf::from(part));
+        }
+
         let mut popped = self.clone();
         if popped.pop() {
-            shrunk.push(popped);
+            push(popped);
         }
 
         // Iterating over a Path performs a small amount of normalization.
-        let normalized = self.iter().collect::<PathBuf>();
-        if normalized.as_os_str() != self.as_os_str() {
-            shrunk.push(normalized);
-        }
+        push(self.iter().collect::<PathBuf>());
 
         // Add the canonicalized variant only if canonicalizing the path
         // actually does something, making it (hopefully) smaller. Also, ignore
         // canonicalization if canonicalization errors.
+        #[cfg(not(target_arch = "wasm32"))]
         if let Ok(canonicalized) = self.canonicalize() {
-            if canonicalized.as_os_str() != self.as_os_str() {
-                shrunk.push(canonicalized);
-            }
+            push(canonicalized);
         }
 
         Box::new(shrunk.into_iter())
@@ -573,10 +1867,7 @@ impl Arbitrary for OsString {
 
 impl Arbitrary for String {
     fn arbitrary(g: &mut Gen) -> String {
-        let size = {
-            let s = g.size();
-            g.gen_range(0..s)
-        };
+        let size = g.gen_edge_len();
         (0..size).map(|_| char::arbitrary(g)).collect()
     }
 
@@ -589,10 +1880,7 @@ impl Arbitrary for String {
 
 impl Arbitrary for CString {
     fn arbitrary(g: &mut Gen) -> Self {
-        let size = {
-            let s = g.size();
-            g.gen_range(0..s)
-        };
+        let size = g.gen_len();
         // Use either random bytes or random UTF-8 encoded codepoints.
         let utf8: bool = g.gen();
         if utf8 {
@@ -616,29 +1904,219 @@ impl Arbitrary for CString {
     }
 
     fn shrink(&self) -> Box<dyn Iterator<Item = CString>> {
-        // Use the implementation for a vec here, but make sure null characters
-        // are filtered out.
-        Box::new(VecShrinker::new(self.as_bytes().to_vec()).map(|bytes| {
+        // Use the implementation for a vec of non-zero bytes here, so that
+        // shrunk bytes are never null. Unwrapping the bytes reuses the
+        // allocation of the shrunk vec.
+        let bytes = self.as_bytes().iter().filter_map(|&b| NonZeroU8::new(b));
+        Box::new(VecShrinker::new(bytes.collect()).map(|bytes| {
             CString::new(
-                bytes.into_iter().filter(|&c| c != 0).collect::<Vec<u8>>(),
+                bytes.into_iter().map(NonZeroU8::get).collect::<Vec<u8>>(),
             )
-            .expect("null characters should have been filtered out")
+            .expect("shrunk bytes should never be null")
         }))
     }
 }
 
+/// Static strings generated for `&'static str` and `Cow::Borrowed` values.
+///
+/// Simpler strings come first, since `&'static str` shrinks toward earlier
+/// entries.
+const STATIC_STRS: &[&str] = &[
+    "",
+    "a",
+    " ",
+    "\n",
+    "\t",
+    "0",
+    "\r\n",
+    "-1",
+    "  ",
+    " a ",
+    "hello",
+    "Hello, World!",
+    "null",
+    "true",
+    "NaN",
+    "\u{a0}",
+    "\u{feff}",
+    "\u{200b}",
+    "é",
+    "e\u{301}",
+    "ß",
+    "\u{1F600}",
+    "\u{1F468}\u{200d}\u{1F469}\u{200d}\u{1F467}",
+    "日本語",
+    "\u{202e}abc",
+    "%s",
+    "%d%n",
+    "{}",
+    "{:?}",
+    "${HOME}",
+    "\\",
+    "/",
+    ".",
+    "..",
+    "../../..",
+    "/etc/passwd",
+    "C:\\Windows\\System32",
+    "a/b/c.txt",
+    "'",
+    "\"",
+    "' OR '1'='1",
+    "'; DROP TABLE users; --",
+    "<script>alert(1)</script>",
+    "9223372036854775808",
+    "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+];
+
+/// Generates strings from a fixed table of interesting strings, such as
+/// empty, whitespace, non-ASCII, format specifier-like, path-like and
+/// SQL-like strings. No other strings are ever generated, since arbitrary
+/// `'static` strings can't be made without leaking memory. Use
+/// `wrappers::LeakedString` for arbitrary contents.
+///
+/// Shrinking yields the earlier entries of the table that are no longer
+/// than the string.
+impl Arbitrary for &'static str {
+    fn arbitrary(g: &mut Gen) -> &'static str {
+        g.choose(STATIC_STRS).unwrap()
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = &'static str>> {
+        let s = *self;
+        let len = s.len();
+        let index = STATIC_STRS
+            .iter()
+            .position(|&t| t == s)
+            .unwrap_or(STATIC_STRS.len());
+        Box::new(
+            STATIC_STRS[..index]
+                .iter()
+                .cloned()
+                .filter(move |t| t.len() <= len),
+        )
+    }
+}
+
+impl Arbitrary for Cow<'static, str> {
+    fn arbitrary(g: &mut Gen) -> Self {
+        if g.gen() {
+            Cow::Borrowed(*g.choose(STATIC_STRS).unwrap())
+        } else {
+            Cow::Owned(String::arbitrary(g))
+        }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        match *self {
+            Cow::Borrowed(s) => Box::new(
+                STATIC_STRS
+                    .iter()
+                    .filter(move |c| c.len() < s.len())
+                    .min_by_key(|c| c.len())
+                    .map(|&c| Cow::Borrowed(c))
+                    .into_iter(),
+            ),
+            Cow::Owned(ref s) => Box::new(s.shrink().map(Cow::Owned)),
+        }
+    }
+}
+
+/// Static byte strings used for `Cow::Borrowed` values.
+const COW_BYTES: &[&[u8]] = &[b"", b"\0", b"\n", b"hello", b"\xFF\xFE"];
+
+impl Arbitrary for Cow<'static, [u8]> {
+    fn arbitrary(g: &mut Gen) -> Self {
+        if g.gen() {
+            Cow::Borrowed(*g.choose(COW_BYTES).unwrap())
+        } else {
+            Cow::Owned(Vec::arbitrary(g))
+        }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        match *self {
+            Cow::Borrowed(s) => Box::new(
+                COW_BYTES
+                    .iter()
+                    .filter(move |c| c.len() < s.len())
+                    .min_by_key(|c| c.len())
+                    .map(|&c| Cow::Borrowed(c))
+                    .into_iter(),
+            ),
+            Cow::Owned(ref s) => Box::new(s.shrink().map(Cow::Owned)),
+        }
+    }
+}
+
+/// Characters that text handling code often gets wrong.
+const TRICKY_CHARS: &[char] = &[
+    '\0',       // confused with a C string terminator
+    '\u{0149}', // a deprecated character
+    '\u{fff0}', // some of "Other, format" category:
+    '\u{fff1}',
+    '\u{fff2}',
+    '\u{fff3}',
+    '\u{fff4}',
+    '\u{fff5}',
+    '\u{fff6}',
+    '\u{fff7}',
+    '\u{fff8}',
+    '\u{fff9}',
+    '\u{fffA}',
+    '\u{fffB}',
+    '\u{fffC}',
+    '\u{fffD}',
+    '\u{fffE}',
+    '\u{fffF}',
+    '\u{0600}',
+    '\u{0601}',
+    '\u{0602}',
+    '\u{0603}',
+    '\u{0604}',
+    '\u{0605}',
+    '\u{061C}',
+    '\u{06DD}',
+    '\u{070F}',
+    '\u{180E}',
+    '\u{110BD}',
+    '\u{1D173}',
+    '\u{e0001}', // tag
+    '\u{e0020}', //  tag space
+    '\u{e000}',
+    '\u{e001}',
+    '\u{ef8ff}', // private use
+    '\u{f0000}',
+    '\u{ffffd}',
+    '\u{ffffe}',
+    '\u{fffff}',
+    '\u{100000}',
+    '\u{10FFFD}',
+    '\u{10FFFE}',
+    '\u{10FFFF}',
+    // "Other, surrogate" characters are so that very special
+    // that they are not even allowed in safe Rust,
+    //so omitted here
+    '\u{3000}', // ideographic space
+    '\u{1680}',
+    // other space characters are already covered by other branches of
+    // `char::arbitrary`
+];
+
 impl Arbitrary for char {
     fn arbitrary(g: &mut Gen) -> char {
-        let mode = g.gen_range(0..100);
+        let mode = g.gen_value_range(0..100);
         match mode {
             0..=49 => {
                 // ASCII + some control characters
-                g.gen_range(0..0xB0) as u8 as char
+                g.gen_value_range(0..0xB0) as u8 as char
             }
             50..=59 => {
                 // Unicode BMP characters
                 loop {
-                    if let Some(x) = char::from_u32(g.gen_range(0..0x10000)) {
+                    if let Some(x) =
+                        char::from_u32(g.gen_value_range(0..0x10000))
+                    {
                         return x;
                     }
                     // ignore surrogate pairs
@@ -658,63 +2136,11 @@ impl Arbitrary for char {
             }
             85..=89 => {
                 // Tricky Unicode, part 1
-                g.choose(&[
-                    '\u{0149}', // a deprecated character
-                    '\u{fff0}', // some of "Other, format" category:
-                    '\u{fff1}',
-                    '\u{fff2}',
-                    '\u{fff3}',
-                    '\u{fff4}',
-                    '\u{fff5}',
-                    '\u{fff6}',
-                    '\u{fff7}',
-                    '\u{fff8}',
-                    '\u{fff9}',
-                    '\u{fffA}',
-                    '\u{fffB}',
-                    '\u{fffC}',
-                    '\u{fffD}',
-                    '\u{fffE}',
-                    '\u{fffF}',
-                    '\u{0600}',
-                    '\u{0601}',
-                    '\u{0602}',
-                    '\u{0603}',
-                    '\u{0604}',
-                    '\u{0605}',
-                    '\u{061C}',
-                    '\u{06DD}',
-                    '\u{070F}',
-                    '\u{180E}',
-                    '\u{110BD}',
-                    '\u{1D173}',
-                    '\u{e0001}', // tag
-                    '\u{e0020}', //  tag space
-                    '\u{e000}',
-                    '\u{e001}',
-                    '\u{ef8ff}', // private use
-                    '\u{f0000}',
-                    '\u{ffffd}',
-                    '\u{ffffe}',
-                    '\u{fffff}',
-                    '\u{100000}',
-                    '\u{10FFFD}',
-                    '\u{10FFFE}',
-                    '\u{10FFFF}',
-                    // "Other, surrogate" characters are so that very special
-                    // that they are not even allowed in safe Rust,
-                    //so omitted here
-                    '\u{3000}', // ideographic space
-                    '\u{1680}',
-                    // other space characters are already covered by two next
-                    // branches
-                ])
-                .unwrap()
-                .to_owned()
+                *g.choose(TRICKY_CHARS).unwrap()
             }
             90..=94 => {
                 // Tricky unicode, part 2
-                char::from_u32(g.gen_range(0x2000..0x2070)).unwrap()
+                char::from_u32(g.gen_value_range(0x2000..0x2070)).unwrap()
             }
             95..=99 => {
                 // Completely arbitrary characters
@@ -725,13 +2151,34 @@ impl Arbitrary for char {
     }
 
     fn shrink(&self) -> Box<dyn Iterator<Item = char>> {
-        Box::new((*self as u32).shrink().filter_map(char::from_u32))
+        // Try 'a' before the characters with smaller code points, which
+        // start with '\0', since it's more readable than any of them. Every
+        // candidate has a smaller code point, so shrinking terminates.
+        let a = Some('a').filter(|&a| *self > a);
+        let smaller = (*self as u32)
+            .shrink()
+            .filter_map(char::from_u32)
+            .filter(move |&c| Some(c) != a);
+        Box::new(a.into_iter().chain(smaller))
     }
 }
 
+/// Small values that large integers shrink to before halving the distance
+/// to zero.
+///
+/// Minimal counterexamples are usually small, but halving takes dozens of
+/// steps (each running the property) to get from a value like `u64::MAX`
+/// down to one. So values that don't fit in the smaller integer types first
+/// try these. Values that do fit are only halved, since retrying the ladder
+/// at every step would make shrinking them slower.
+const SHRINK_LADDER: &[u32] =
+    &[1, 2, 3, 7, 10, 16, 100, 255, 256, 65535, 65536];
+
 macro_rules! unsigned_shrinker {
     ($ty:ty) => {
         mod shrinker {
+            use std::convert::TryFrom;
+
             pub struct UnsignedShrinker {
                 x: $ty,
                 i: $ty,
@@ -742,10 +2189,19 @@ macro_rules! unsigned_shrinker {
                     if x == 0 {
                         super::empty_shrinker()
                     } else {
+                        let mut items = vec![0];
+                        let ladder = super::SHRINK_LADDER;
+                        if <$ty>::try_from(ladder[ladder.len() - 1])
+                            .map_or(false, |max| x > max)
+                        {
+                            items.extend(ladder.iter().map(|&k| k as $ty));
+                        }
+                        let tried = items.clone();
                         Box::new(
-                            vec![0]
-                                .into_iter()
-                                .chain(UnsignedShrinker { x: x, i: x / 2 }),
+                            items.into_iter().chain(
+                                UnsignedShrinker { x: x, i: x / 2 }
+                                    .filter(move |y| !tried.contains(y)),
+                            ),
                         )
                     }
                 }
@@ -767,10 +2223,41 @@ macro_rules! unsigned_shrinker {
     };
 }
 
+// Types wider than 32 bits also get the values around 2^31 and 2^32, where
+// code that converts between them and `u32` or `i32` breaks. Without these,
+// such values would almost never be generated for a 64-bit `usize`.
 macro_rules! unsigned_problem_values {
-    ($t:ty) => {
+    (usize) => {{
+        #[cfg(target_pointer_width = "64")]
+        const VALUES: &[usize] = unsigned_problem_values!(@wide usize);
+        #[cfg(not(target_pointer_width = "64"))]
+        const VALUES: &[usize] = unsigned_problem_values!(@narrow usize);
+        VALUES
+    }};
+    (u64) => {
+        unsigned_problem_values!(@wide u64)
+    };
+    (u128) => {
+        unsigned_problem_values!(@wide u128)
+    };
+    ($t:tt) => {
+        unsigned_problem_values!(@narrow $t)
+    };
+    (@narrow $t:ty) => {
         &[<$t>::min_value(), 1, <$t>::max_value()]
     };
+    (@wide $t:ty) => {
+        &[
+            <$t>::min_value(),
+            1,
+            <$t>::max_value(),
+            0x7fff_ffff,
+            0x8000_0000,
+            0xffff_ffff,
+            0x1_0000_0000,
+            0x1_0000_0001,
+        ]
+    };
 }
 
 macro_rules! unsigned_arbitrary {
@@ -778,7 +2265,7 @@ macro_rules! unsigned_arbitrary {
         $(
             impl Arbitrary for $ty {
                 fn arbitrary(g: &mut Gen) -> $ty {
-                    match g.gen_range(0..10) {
+                    match g.gen_value_range(0..10) {
                         0 => {
                             *g.choose(unsigned_problem_values!($ty)).unwrap()
                         },
@@ -801,6 +2288,8 @@ unsigned_arbitrary! {
 macro_rules! signed_shrinker {
     ($ty:ty) => {
         mod shrinker {
+            use std::convert::TryFrom;
+
             pub struct SignedShrinker {
                 x: $ty,
                 i: $ty,
@@ -816,7 +2305,21 @@ macro_rules! signed_shrinker {
                         if shrinker.i < 0 && shrinker.x != <$ty>::MIN {
                             items.push(shrinker.x.abs());
                         }
-                        Box::new(items.into_iter().chain(shrinker))
+                        let ladder = super::SHRINK_LADDER;
+                        if <$ty>::try_from(ladder[ladder.len() - 1])
+                            .map_or(false, |max| {
+                                x == <$ty>::MIN || x.abs() > max
+                            })
+                        {
+                            let sign = if x < 0 { -1 } else { 1 };
+                            items.extend(
+                                ladder.iter().map(|&k| sign * k as $ty),
+                            );
+                        }
+                        let tried = items.clone();
+                        Box::new(items.into_iter().chain(
+                            shrinker.filter(move |y| !tried.contains(y)),
+                        ))
                     }
                 }
             }
@@ -824,9 +2327,15 @@ macro_rules! signed_shrinker {
             impl Iterator for SignedShrinker {
                 type Item = $ty;
                 fn next(&mut self) -> Option<$ty> {
-                    if self.x == <$ty>::MIN
-                        || (self.x - self.i).abs() < self.x.abs()
-                    {
+                    // `MIN` has no absolute value, but every candidate
+                    // closer to zero is smaller. Stop once the step reaches
+                    // zero, since that would yield `MIN` itself (forever).
+                    let smaller = if self.x == <$ty>::MIN {
+                        self.i != 0
+                    } else {
+                        (self.x - self.i).abs() < self.x.abs()
+                    };
+                    if smaller {
                         let result = Some(self.x - self.i);
                         self.i = self.i / 2;
                         result
@@ -839,10 +2348,39 @@ macro_rules! signed_shrinker {
     };
 }
 
+// See `unsigned_problem_values`.
 macro_rules! signed_problem_values {
-    ($t:ty) => {
+    (isize) => {{
+        #[cfg(target_pointer_width = "64")]
+        const VALUES: &[isize] = signed_problem_values!(@wide isize);
+        #[cfg(not(target_pointer_width = "64"))]
+        const VALUES: &[isize] = signed_problem_values!(@narrow isize);
+        VALUES
+    }};
+    (i64) => {
+        signed_problem_values!(@wide i64)
+    };
+    (i128) => {
+        signed_problem_values!(@wide i128)
+    };
+    ($t:tt) => {
+        signed_problem_values!(@narrow $t)
+    };
+    (@narrow $t:ty) => {
         &[<$t>::min_value(), 0, <$t>::max_value()]
     };
+    (@wide $t:ty) => {
+        &[
+            <$t>::min_value(),
+            0,
+            <$t>::max_value(),
+            0x7fff_ffff,
+            0x8000_0000,
+            0xffff_ffff,
+            0x1_0000_0000,
+            0x1_0000_0001,
+        ]
+    };
 }
 
 macro_rules! signed_arbitrary {
@@ -850,7 +2388,7 @@ macro_rules! signed_arbitrary {
         $(
             impl Arbitrary for $ty {
                 fn arbitrary(g: &mut Gen) -> $ty {
-                    match g.gen_range(0..10) {
+                    match g.gen_value_range(0..10) {
                         0 => {
                             *g.choose(signed_problem_values!($ty)).unwrap()
                         },
@@ -882,12 +2420,14 @@ macro_rules! float_arbitrary {
     ($($t:ty, $path:path, $shrinkable:ty),+) => {$(
         impl Arbitrary for $t {
             fn arbitrary(g: &mut Gen) -> $t {
-                match g.gen_range(0..10) {
+                match g.gen_value_range(0..10) {
                     0 => *g.choose(float_problem_values!($path)).unwrap(),
                     _ => {
                         use $path as p;
-                        let exp = g.gen_range((0.)..p::MAX_EXP as i16 as $t);
-                        let mantissa = g.gen_range((1.)..2.);
+                        let exp = g.gen_value_range(
+                            (0.)..p::MAX_EXP as i16 as $t,
+                        );
+                        let mantissa = g.gen_value_range((1.)..2.);
                         let sign = *g.choose(&[-1., 1.]).unwrap();
                         sign * mantissa * exp.exp2()
                     }
@@ -988,7 +2528,7 @@ impl<T: Arbitrary> Arbitrary for Wrapping<T> {
 
 impl<T: Arbitrary> Arbitrary for Bound<T> {
     fn arbitrary(g: &mut Gen) -> Bound<T> {
-        match g.gen_range(0..3) {
+        match g.gen_value_range(0..3) {
             0 => Bound::Included(T::arbitrary(g)),
             1 => Bound::Excluded(T::arbitrary(g)),
             _ => Bound::Unbounded,
@@ -1007,6 +2547,8 @@ impl<T: Arbitrary> Arbitrary for Bound<T> {
     }
 }
 
+/// Both bounds are generated and shrunk independently, so the range may be
+/// inverted. Use `wrappers::SortedRange` for ranges with `start <= end`.
 impl<T: Arbitrary + Clone + PartialOrd> Arbitrary for Range<T> {
     fn arbitrary(g: &mut Gen) -> Range<T> {
         Arbitrary::arbitrary(g)..Arbitrary::arbitrary(g)
@@ -1018,6 +2560,8 @@ impl<T: Arbitrary + Clone + PartialOrd> Arbitrary for Range<T> {
     }
 }
 
+/// Both bounds are generated and shrunk independently, so the range may be
+/// inverted. Use `wrappers::SortedRange` for ranges with `start <= end`.
 impl<T: Arbitrary + Clone + PartialOrd> Arbitrary for RangeInclusive<T> {
     fn arbitrary(g: &mut Gen) -> RangeInclusive<T> {
         Arbitrary::arbitrary(g)..=Arbitrary::arbitrary(g)
@@ -1064,19 +2608,92 @@ impl Arbitrary for RangeFull {
     }
 }
 
+/// Durations at the boundaries of the seconds and nanoseconds fields.
+const DURATION_PROBLEM_VALUES: &[Duration] = &[
+    Duration::ZERO,
+    Duration::from_nanos(1),
+    Duration::from_nanos(999_999_999),
+    Duration::from_secs(1),
+    Duration::MAX,
+];
+
 impl Arbitrary for Duration {
     fn arbitrary(gen: &mut Gen) -> Self {
-        let seconds = gen.gen_range(0..gen.size() as u64);
-        let nanoseconds = gen.gen_range(0..1_000_000);
+        if gen.gen_value_range(0..10) == 0 {
+            return *gen.choose(DURATION_PROBLEM_VALUES).unwrap();
+        }
+        let seconds = u64::arbitrary(gen);
+        let nanoseconds = gen.gen_value_range(0..1_000_000_000);
         Duration::new(seconds, nanoseconds)
     }
 
     fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
-        Box::new(
-            (self.as_secs(), self.subsec_nanos())
-                .shrink()
-                .map(|(secs, nanos)| Duration::new(secs, nanos % 1_000_000)),
-        )
+        if *self == Duration::ZERO {
+            return empty_shrinker();
+        }
+        let rest = (self.as_secs(), self.subsec_nanos())
+            .shrink()
+            .map(|(secs, nanos)| Duration::new(secs, nanos))
+            .filter(|&d| d != Duration::ZERO);
+        Box::new(once(Duration::ZERO).chain(rest))
+    }
+}
+
+/// Shrinking defers to the shrinker of the wrapped value, so a `Reverse<T>`
+/// shrinks toward simpler values of `T` (for example, toward `0` for
+/// integers). Note that under `Reverse`'s ordering, these simpler values
+/// usually compare as *greater* than the original.
+impl<T: Arbitrary> Arbitrary for Reverse<T> {
+    fn arbitrary(g: &mut Gen) -> Reverse<T> {
+        Reverse(T::arbitrary(g))
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Reverse<T>>> {
+        Box::new(self.0.shrink().map(Reverse))
+    }
+}
+
+/// `Infallible` has no values, so this impl only exists to satisfy trait
+/// bounds, e.g., of `Result<T, Infallible>`. Calling `arbitrary` panics.
+impl Arbitrary for Infallible {
+    fn arbitrary(_: &mut Gen) -> Infallible {
+        unreachable!("Infallible has no values to generate")
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Infallible>> {
+        empty_shrinker()
+    }
+}
+
+impl Arbitrary for TryFromIntError {
+    fn arbitrary(_: &mut Gen) -> TryFromIntError {
+        i32::try_from(u64::MAX).unwrap_err()
+    }
+}
+
+impl Arbitrary for CharTryFromError {
+    fn arbitrary(_: &mut Gen) -> CharTryFromError {
+        char::try_from(0xD800u32).unwrap_err()
+    }
+}
+
+impl Arbitrary for TryFromSliceError {
+    fn arbitrary(_: &mut Gen) -> TryFromSliceError {
+        <[u8; 1]>::try_from(&[][..]).unwrap_err()
+    }
+}
+
+/// Always generates an initialized value, so that it is sound to call
+/// `assume_init` on it.
+///
+/// Uninitialized values are never generated: since a `MaybeUninit` doesn't
+/// record whether it has been initialized, a property couldn't tell them
+/// apart without undefined behavior. Values of this type are never shrunk
+/// either. (`MaybeUninit<T>` is only `Clone` when `T` is `Copy`, hence the
+/// bound.)
+impl<T: Arbitrary + Copy> Arbitrary for MaybeUninit<T> {
+    fn arbitrary(g: &mut Gen) -> MaybeUninit<T> {
+        MaybeUninit::new(T::arbitrary(g))
     }
 }
 
@@ -1095,54 +2712,655 @@ impl<A: Arbitrary + Sync> Arbitrary for Arc<A> {
         Arc::new(A::arbitrary(g))
     }
 
-    fn shrink(&self) -> Box<dyn Iterator<Item = Arc<A>>> {
-        Box::new((**self).shrink().map(Arc::new))
+    fn shrink(&self) -> Box<dyn Iterator<Item = Arc<A>>> {
+        Box::new((**self).shrink().map(Arc::new))
+    }
+}
+
+impl Arbitrary for Arc<str> {
+    fn arbitrary(g: &mut Gen) -> Arc<str> {
+        Arc::from(String::arbitrary(g))
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Arc<str>>> {
+        Box::new(self.to_string().shrink().map(Arc::from))
+    }
+}
+
+/// The number of seconds before and after the Unix epoch that a
+/// `SystemTime` can represent on this platform.
+///
+/// On Windows, a `SystemTime` counts 100ns intervals since 1601 in an `i64`.
+#[cfg(windows)]
+const SYSTEM_TIME_SECS_BEFORE_EPOCH: u64 = 11_644_473_600;
+#[cfg(windows)]
+const SYSTEM_TIME_SECS_AFTER_EPOCH: u64 =
+    i64::MAX as u64 / 10_000_000 - SYSTEM_TIME_SECS_BEFORE_EPOCH;
+/// On wasm32-unknown-unknown, a `SystemTime` is a `Duration` since the
+/// epoch, so times before it can't be represented.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+const SYSTEM_TIME_SECS_BEFORE_EPOCH: u64 = 0;
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+const SYSTEM_TIME_SECS_AFTER_EPOCH: u64 = i64::MAX as u64;
+/// Elsewhere, a `SystemTime` is usually a number of seconds since the epoch
+/// in an `i64`.
+#[cfg(not(any(windows, all(target_arch = "wasm32", target_os = "unknown"))))]
+const SYSTEM_TIME_SECS_BEFORE_EPOCH: u64 = i64::MAX as u64;
+#[cfg(not(any(windows, all(target_arch = "wasm32", target_os = "unknown"))))]
+const SYSTEM_TIME_SECS_AFTER_EPOCH: u64 = i64::MAX as u64;
+
+impl Arbitrary for SystemTime {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        // Generate durations from the epoch within the representable
+        // window, including its edges. Since the window is only known for
+        // some platforms, fall back to the epoch if a duration can't be
+        // added to or subtracted from it anyway.
+        let after_epoch =
+            bool::arbitrary(gen) || SYSTEM_TIME_SECS_BEFORE_EPOCH == 0;
+        let limit = if after_epoch {
+            SYSTEM_TIME_SECS_AFTER_EPOCH
+        } else {
+            SYSTEM_TIME_SECS_BEFORE_EPOCH
+        };
+        let duration = if gen.gen_value_range(0..10) == 0 {
+            Duration::from_secs(limit)
+        } else {
+            let d = Duration::arbitrary(gen);
+            Duration::new(d.as_secs() % limit, d.subsec_nanos())
+        };
+        let time = if after_epoch {
+            UNIX_EPOCH.checked_add(duration)
+        } else {
+            UNIX_EPOCH.checked_sub(duration)
+        };
+        time.unwrap_or(UNIX_EPOCH)
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let duration = match self.duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration,
+            Err(e) => e.duration(),
+        };
+        Box::new(duration.shrink().flat_map(|d| {
+            UNIX_EPOCH
+                .checked_add(d)
+                .into_iter()
+                .chain(UNIX_EPOCH.checked_sub(d))
+        }))
+    }
+}
+
+/// Implements `DistributionInfo` for types that don't contain other
+/// generated types, given their special case probability and whether they
+/// respect the size.
+macro_rules! distribution_info {
+    ($($ty:ty => ($probability:expr, $sized:expr),)*) => {$(
+        impl DistributionInfo for $ty {
+            fn special_case_probability() -> f64 {
+                $probability
+            }
+
+            fn respects_size() -> bool {
+                $sized
+            }
+        }
+    )*};
+}
+
+distribution_info! {
+    () => (0.0, false),
+    bool => (0.0, false),
+    char => (0.1, false),
+    u8 => (0.1, false),
+    u16 => (0.1, false),
+    u32 => (0.1, false),
+    u64 => (0.1, false),
+    u128 => (0.1, false),
+    usize => (0.1, false),
+    i8 => (0.1, false),
+    i16 => (0.1, false),
+    i32 => (0.1, false),
+    i64 => (0.1, false),
+    i128 => (0.1, false),
+    isize => (0.1, false),
+    f32 => (0.1, false),
+    f64 => (0.1, false),
+    NonZeroU8 => (0.0, false),
+    NonZeroU16 => (0.0, false),
+    NonZeroU32 => (0.0, false),
+    NonZeroU64 => (0.0, false),
+    NonZeroU128 => (0.0, false),
+    NonZeroUsize => (0.0, false),
+    IpAddr => (0.1, false),
+    Ipv4Addr => (0.1, false),
+    Ipv6Addr => (0.1, false),
+    SocketAddr => (0.1, false),
+    SocketAddrV4 => (0.1, false),
+    SocketAddrV6 => (0.1, false),
+    Duration => (0.1, false),
+    SystemTime => (0.1, false),
+    RangeFull => (0.0, false),
+    Infallible => (0.0, false),
+    TryFromIntError => (0.0, false),
+    CharTryFromError => (0.0, false),
+    TryFromSliceError => (0.0, false),
+    String => (0.2, true),
+    OsString => (0.2, true),
+    Arc<str> => (0.2, true),
+    CString => (0.0, true),
+    PathBuf => (0.0, true),
+    &'static str => (0.0, false),
+    Cow<'static, str> => (0.5, true),
+    Cow<'static, [u8]> => (0.5, true),
+}
+
+/// Implements `DistributionInfo` for types that generate a single value of
+/// another type, and have the same distribution.
+macro_rules! distribution_info_like {
+    ($($ty:ident<$param:ident $(: $bound:path)*>,)*) => {$(
+        impl<$param> DistributionInfo for $ty<$param>
+        where
+            $param: DistributionInfo $(+ $bound)*,
+        {
+            fn special_case_probability() -> f64 {
+                $param::special_case_probability()
+            }
+
+            fn respects_size() -> bool {
+                $param::respects_size()
+            }
+        }
+    )*};
+}
+
+distribution_info_like! {
+    Box<A>,
+    Arc<A: Sync>,
+    Wrapping<T>,
+    Reverse<T>,
+    RangeFrom<T: PartialOrd>,
+    RangeTo<T: PartialOrd>,
+    RangeToInclusive<T: PartialOrd>,
+}
+
+/// Implements `DistributionInfo` for types that generate several values of
+/// another type, and have no special cases of their own.
+macro_rules! distribution_info_values {
+    ($($ty:ident<$param:ident: $bound:path>,)*) => {$(
+        impl<$param> DistributionInfo for $ty<$param>
+        where
+            $param: DistributionInfo + $bound,
+        {
+            fn respects_size() -> bool {
+                $param::respects_size()
+            }
+        }
+    )*};
+}
+
+distribution_info_values! {
+    Range<T: PartialOrd>,
+    RangeInclusive<T: PartialOrd>,
+}
+
+/// The probability is that of `Unbounded`.
+impl<T: DistributionInfo> DistributionInfo for Bound<T> {
+    fn special_case_probability() -> f64 {
+        1.0 / 3.0
+    }
+
+    fn respects_size() -> bool {
+        T::respects_size()
+    }
+}
+
+/// Nothing is known about the installed generator, so this reports the
+/// defaults.
+impl<T: Clone + 'static> DistributionInfo for Registered<T> {}
+
+/// Nothing is known about the installed generator, so this reports the
+/// defaults.
+impl<T: 'static> DistributionInfo for ArcArbitrary<T> {}
+
+/// Implements `DistributionInfo` for collections, which have no special
+/// cases and choose their length with the size.
+macro_rules! distribution_info_collection {
+    ($($ty:ident<$($param:ident),*> [$($bound:tt)*],)*) => {$(
+        impl<$($param),*> DistributionInfo for $ty<$($param),*>
+        where
+            $($bound)*
+        {
+            fn respects_size() -> bool {
+                true
+            }
+        }
+    )*};
+}
+
+distribution_info_collection! {
+    Vec<A> [A: Arbitrary],
+    VecDeque<T> [T: Arbitrary],
+    LinkedList<T> [T: Arbitrary],
+    BinaryHeap<T> [T: Arbitrary + Ord],
+    BTreeSet<T> [T: Arbitrary + Ord],
+    BTreeMap<K, V> [K: Arbitrary + Ord, V: Arbitrary],
+    HashSet<T, S> [
+        T: Arbitrary + Eq + Hash,
+        S: BuildHasher + Default + Clone + 'static,
+    ],
+    HashMap<K, V, S> [
+        K: Arbitrary + Eq + Hash,
+        V: Arbitrary,
+        S: BuildHasher + Default + Clone + 'static,
+    ],
+}
+
+/// Arrays have no special cases of their own, and only depend on the size
+/// through their elements.
+impl<A: DistributionInfo, const N: usize> DistributionInfo for [A; N] {
+    fn respects_size() -> bool {
+        A::respects_size()
+    }
+}
+
+/// The probability is that of `None`. Only the size dependence of `A` is
+/// reported, since its special cases are not special cases of the option.
+impl<A: DistributionInfo> DistributionInfo for Option<A> {
+    fn special_case_probability() -> f64 {
+        DEFAULT_NONE_RATIO
+    }
+
+    fn respects_size() -> bool {
+        A::respects_size()
+    }
+}
+
+/// The probability is that of `Err`.
+impl<A: DistributionInfo, B: DistributionInfo> DistributionInfo
+    for Result<A, B>
+{
+    fn special_case_probability() -> f64 {
+        if is_infallible::<B>() {
+            0.0
+        } else if is_infallible::<A>() {
+            1.0
+        } else {
+            DEFAULT_ERR_RATIO
+        }
+    }
+
+    fn respects_size() -> bool {
+        A::respects_size() || B::respects_size()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{
+        BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque,
+    };
+    use std::fmt::Debug;
+    use std::hash::Hash;
+    use std::num::Wrapping;
+    use std::path::PathBuf;
+
+    use super::{Arbitrary, Gen};
+
+    #[test]
+    fn arby_unit() {
+        assert_eq!(arby::<()>(), ());
+    }
+
+    #[test]
+    fn arby_char_nul() {
+        let arbys: Vec<char> = (0..10_000).map(|_| arby()).collect();
+        assert!(arbys.contains(&'\0'), "'\\0' was never generated");
+    }
+
+    #[test]
+    fn arby_duration_problem_values() {
+        use std::time::Duration;
+
+        let arbys: Vec<Duration> = (0..10_000).map(|_| arby()).collect();
+        for p in super::DURATION_PROBLEM_VALUES {
+            assert!(arbys.contains(p), "{:?} was never generated", p);
+        }
+    }
+
+    /// Checks that the declared special case probability of `A` matches
+    /// the frequency of values satisfying `special` in a large sample.
+    fn check_special_cases<A>(special: impl Fn(&A) -> bool)
+    where
+        A: super::DistributionInfo,
+    {
+        check_special_cases_by_chance(special, 0.0);
+    }
+
+    /// Like `check_special_cases`, for types whose other branches generate
+    /// a special case with probability `chance`.
+    fn check_special_cases_by_chance<A>(
+        special: impl Fn(&A) -> bool,
+        chance: f64,
+    ) where
+        A: super::DistributionInfo,
+    {
+        let mut g = Gen::from_seed(100, 0);
+        let n = 20_000;
+        let count = (0..n).filter(|_| special(&A::arbitrary(&mut g))).count();
+        let observed = count as f64 / n as f64;
+        let declared = A::special_case_probability();
+        let expected = declared + (1.0 - declared) * chance;
+        assert!(
+            (observed - expected).abs() < 0.01,
+            "{}: declared {}, observed {}",
+            std::any::type_name::<A>(),
+            declared,
+            observed
+        );
+    }
+
+    #[test]
+    fn declared_special_case_probabilities() {
+        use std::borrow::Cow;
+        use std::convert::Infallible;
+        use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+        use std::num::NonZeroU32;
+        use std::ops::{Bound, Range, RangeFrom};
+        use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+        use super::{
+            DURATION_PROBLEM_VALUES, IPV4_PROBLEM_VALUES, IPV6_PROBLEM_VALUES,
+            SYSTEM_TIME_SECS_AFTER_EPOCH, SYSTEM_TIME_SECS_BEFORE_EPOCH,
+            TRICKY_CHARS,
+        };
+        use crate::combinators::VecAndIndex;
+        use crate::text::Identifier;
+        use crate::wrappers::{
+            gcd, BoundedBound, LazyArbitrary, LeakedString, Probability,
+            Ratio, SortedRange, TotalF64, PROBABILITY_PROBLEM_VALUES,
+        };
+
+        fn is_problem_ip(ip: &IpAddr) -> bool {
+            match *ip {
+                IpAddr::V4(ref ip) => IPV4_PROBLEM_VALUES.contains(ip),
+                IpAddr::V6(ref ip) => IPV6_PROBLEM_VALUES.contains(ip),
+            }
+        }
+
+        check_special_cases(|x: &u64| {
+            unsigned_problem_values!(u64).contains(x)
+        });
+        check_special_cases(|x: &usize| {
+            unsigned_problem_values!(usize).contains(x)
+        });
+        check_special_cases(|x: &i32| signed_problem_values!(i32).contains(x));
+        check_special_cases_by_chance(
+            |x: &u8| unsigned_problem_values!(u8).contains(x),
+            3.0 / 256.0,
+        );
+        check_special_cases_by_chance(
+            |x: &i8| signed_problem_values!(i8).contains(x),
+            3.0 / 256.0,
+        );
+        check_special_cases_by_chance(
+            |x: &u16| unsigned_problem_values!(u16).contains(x),
+            3.0 / 65_536.0,
+        );
+        check_special_cases_by_chance(
+            |x: &i16| signed_problem_values!(i16).contains(x),
+            3.0 / 65_536.0,
+        );
+        check_special_cases(|x: &u32| {
+            unsigned_problem_values!(u32).contains(x)
+        });
+        check_special_cases(|x: &f64| {
+            let values: &[f64] = float_problem_values!(std::f64);
+            values.iter().any(|p| p.to_bits() == x.to_bits())
+        });
+        check_special_cases(|_: &NonZeroU32| false);
+        check_special_cases(|_: &bool| false);
+        check_special_cases(|c: &char| {
+            TRICKY_CHARS.contains(c) || (0x2000..0x2070).contains(&(*c as u32))
+        });
+        check_special_cases(|x: &Ipv4Addr| IPV4_PROBLEM_VALUES.contains(x));
+        check_special_cases(|x: &Ipv6Addr| IPV6_PROBLEM_VALUES.contains(x));
+        check_special_cases(is_problem_ip);
+        check_special_cases(|x: &SocketAddr| is_problem_ip(&x.ip()));
+        check_special_cases(|x: &Duration| {
+            DURATION_PROBLEM_VALUES.contains(x)
+        });
+        check_special_cases(|t: &SystemTime| {
+            match t.duration_since(UNIX_EPOCH) {
+                Ok(d) => d.as_secs() == SYSTEM_TIME_SECS_AFTER_EPOCH,
+                Err(e) => {
+                    e.duration().as_secs() == SYSTEM_TIME_SECS_BEFORE_EPOCH
+                }
+            }
+        });
+        check_special_cases(|s: &String| {
+            let len = s.chars().count();
+            len == 0 || len == 100
+        });
+        check_special_cases(|x: &Cow<'static, str>| {
+            matches!(*x, Cow::Borrowed(_))
+        });
+        check_special_cases(|x: &Option<u8>| x.is_none());
+        check_special_cases(|x: &Result<u8, u8>| x.is_err());
+        check_special_cases(|x: &Result<u8, Infallible>| x.is_err());
+        check_special_cases(|x: &Result<Infallible, u8>| x.is_err());
+        check_special_cases(|_: &Vec<u8>| false);
+        check_special_cases(|x: &Bound<u8>| *x == Bound::Unbounded);
+        check_special_cases(|x: &RangeFrom<u64>| {
+            unsigned_problem_values!(u64).contains(&x.start)
+        });
+        check_special_cases(|x: &BoundedBound<u64>| match x.0 {
+            Bound::Included(ref x) | Bound::Excluded(ref x) => {
+                unsigned_problem_values!(u64).contains(x)
+            }
+            Bound::Unbounded => true,
+        });
+        check_special_cases(|_: &SortedRange<Range<u64>>| false);
+        check_special_cases(|x: &Probability| {
+            PROBABILITY_PROBLEM_VALUES.contains(&x.get())
+        });
+        // Fractions with small parts that aren't in lowest terms are only
+        // generated by scaling.
+        check_special_cases(|x: &Ratio| {
+            let (n, d) = (x.numerator(), x.denominator().get());
+            n < 1_000_000 && d < 1_000_000 && gcd(n, d) > 1
+        });
+        check_special_cases(|x: &TotalF64| {
+            let values: &[f64] = float_problem_values!(std::f64);
+            values.iter().any(|p| p.to_bits() == x.to_bits())
+        });
+        check_special_cases(|x: &LazyArbitrary<u64>| {
+            unsigned_problem_values!(u64).contains(&x.clone().into_inner())
+        });
+        check_special_cases(|s: &LeakedString| {
+            let len = s.chars().count();
+            len == 0 || len == 100
+        });
+        check_special_cases(|_: &Identifier| false);
+        check_special_cases(|_: &VecAndIndex<u8>| false);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn declared_size_dependence() {
+        use std::borrow::Cow;
+        use std::ffi::{CString, OsString};
+        use std::net::IpAddr;
+        use std::ops::{
+            Bound, Range, RangeFrom, RangeInclusive, RangeTo, RangeToInclusive,
+        };
+        use std::time::{Duration, SystemTime};
+
+        use super::DistributionInfo;
+        use crate::combinators::{MapAndKey, SliceAndRange, VecAndIndex};
+        use crate::text::{
+            Identifier, NumberLiteral, QuotedString, TrickyString, Whitespace,
+        };
+        use crate::wrappers::{
+            BoundedBound, LazyArbitrary, LeakedString, NonEmptyRange,
+            Probability, Ratio, ShrinkableVec, SortedRange, TotalF32,
+            TotalF64,
+        };
+
+        fn check<A: DistributionInfo>() {
+            let mut g = Gen::new(10);
+            for _ in 0..100 {
+                A::arbitrary(&mut g);
+            }
+            assert_eq!(
+                g.size_reads() > 0,
+                A::respects_size(),
+                "{}",
+                std::any::type_name::<A>()
+            );
+        }
+
+        check::<u8>();
+        check::<f64>();
+        check::<char>();
+        check::<IpAddr>();
+        check::<Duration>();
+        check::<SystemTime>();
+        check::<(u8, Option<char>)>();
+        check::<Box<i32>>();
+        check::<String>();
+        check::<OsString>();
+        check::<CString>();
+        check::<PathBuf>();
+        check::<&'static str>();
+        check::<Cow<'static, str>>();
+        check::<Vec<u8>>();
+        check::<[u8; 4]>();
+        check::<[String; 2]>();
+        check::<HashMap<u8, u8>>();
+        check::<BTreeSet<u8>>();
+        check::<VecDeque<u8>>();
+        check::<Option<Vec<u8>>>();
+        check::<(u8, String)>();
+        check::<Bound<u8>>();
+        check::<Bound<String>>();
+        check::<Range<u8>>();
+        check::<RangeInclusive<String>>();
+        check::<RangeFrom<u8>>();
+        check::<RangeTo<String>>();
+        check::<RangeToInclusive<u8>>();
+        check::<SortedRange<Range<u8>>>();
+        check::<NonEmptyRange<String>>();
+        check::<SortedRange<RangeInclusive<u8>>>();
+        check::<BoundedBound<String>>();
+        check::<Probability>();
+        check::<Ratio>();
+        check::<Identifier>();
+        check::<Whitespace>();
+        check::<QuotedString>();
+        check::<TrickyString>();
+        check::<NumberLiteral>();
+        check::<VecAndIndex<u8>>();
+        check::<MapAndKey<u8, u8>>();
+        check::<SliceAndRange<u8>>();
+        check::<TotalF32>();
+        check::<TotalF64>();
+        check::<LazyArbitrary<u8>>();
+        check::<ShrinkableVec<u8>>();
+        check::<LeakedString>();
+    }
+
+    #[test]
+    fn arby_socket_addr_v6() {
+        use std::net::SocketAddrV6;
+
+        let arbys: Vec<SocketAddrV6> = (0..1_000).map(|_| arby()).collect();
+        assert!(arbys.iter().all(|a| a.flowinfo() <= 0xF_FFFF));
+        assert!(arbys.iter().any(|a| a.flowinfo() > 0xFFFF));
+        for scope_id in 0..2 {
+            let count = arbys.iter().filter(|a| a.scope_id() == scope_id);
+            assert!(count.count() > 150, "scope {} is rare", scope_id);
+        }
+        assert!(arbys.iter().any(|a| a.scope_id() > 1));
+    }
+
+    #[test]
+    fn arby_duration_full_range() {
+        use std::time::Duration;
+
+        let arbys: Vec<Duration> = (0..1_000).map(|_| arby()).collect();
+        assert!(arbys.iter().any(|d| d.as_secs() > u64::from(u32::MAX)));
+        assert!(arbys.iter().any(|d| d.subsec_nanos() >= 500_000_000));
+        assert!(arbys.iter().any(|d| d.subsec_nanos() < 500_000_000));
+    }
+
+    #[test]
+    fn durations_shrink_to_zero_first() {
+        use std::time::Duration;
+
+        let d = Duration::new(5, 3);
+        assert_eq!(d.shrink().next(), Some(Duration::ZERO));
+        assert_eq!(d.shrink().filter(|&s| s == Duration::ZERO).count(), 1);
+        assert_eq!(Duration::ZERO.shrink().count(), 0);
     }
-}
 
-impl Arbitrary for SystemTime {
-    fn arbitrary(gen: &mut Gen) -> Self {
-        let after_epoch = bool::arbitrary(gen);
-        let duration = Duration::arbitrary(gen);
-        if after_epoch {
-            UNIX_EPOCH + duration
-        } else {
-            UNIX_EPOCH - duration
+    #[test]
+    fn system_times_stay_in_window() {
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        let after = UNIX_EPOCH
+            + std::time::Duration::from_secs(
+                super::SYSTEM_TIME_SECS_AFTER_EPOCH,
+            );
+        let mut g = Gen::new(100);
+        let mut saw_edge = false;
+        for _ in 0..500_000 {
+            let t = SystemTime::arbitrary(&mut g);
+            saw_edge |= t == after;
+            for s in t.shrink().take(3) {
+                let _ = s.duration_since(UNIX_EPOCH);
+            }
         }
+        assert!(saw_edge);
     }
 
-    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
-        let duration = match self.duration_since(UNIX_EPOCH) {
-            Ok(duration) => duration,
-            Err(e) => e.duration(),
-        };
-        Box::new(
-            duration
-                .shrink()
-                .flat_map(|d| vec![UNIX_EPOCH + d, UNIX_EPOCH - d]),
-        )
+    #[test]
+    fn arby_infallible_results() {
+        use std::convert::Infallible;
+
+        let mut g = Gen::new(10);
+        g.set_err_ratio(1.0);
+        for _ in 0..100 {
+            assert!(Result::<u8, Infallible>::arbitrary(&mut g).is_ok());
+            assert!(Result::<Infallible, u8>::arbitrary(&mut g).is_err());
+        }
+        assert_eq!(
+            Ok::<u8, Infallible>(5).shrink().count(),
+            5u8.shrink().count()
+        );
     }
-}
 
-#[cfg(test)]
-mod test {
-    use std::collections::{
-        BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque,
-    };
-    use std::fmt::Debug;
-    use std::hash::Hash;
-    use std::num::Wrapping;
-    use std::path::PathBuf;
+    #[test]
+    fn arby_ipv4_problem_values() {
+        use std::net::Ipv4Addr;
 
-    use super::{Arbitrary, Gen};
+        let arbys: Vec<Ipv4Addr> = (0..10_000).map(|_| arby()).collect();
+        for p in super::IPV4_PROBLEM_VALUES {
+            assert!(arbys.contains(p), "{} was never generated", p);
+        }
+    }
 
     #[test]
-    fn arby_unit() {
-        assert_eq!(arby::<()>(), ());
+    fn arby_ipv6_problem_values() {
+        use std::net::Ipv6Addr;
+
+        let arbys: Vec<Ipv6Addr> = (0..10_000).map(|_| arby()).collect();
+        for p in super::IPV6_PROBLEM_VALUES {
+            assert!(arbys.contains(p), "{} was never generated", p);
+        }
     }
 
     macro_rules! arby_int {
-        ( $signed:expr, $($t:ty),+) => {$(
+        ( $signed:expr, $($t:tt),+) => {$(
             let mut arbys = (0..1_000_000).map(|_| arby::<$t>());
             let mut problems = if $signed {
                     signed_problem_values!($t).iter()
@@ -1220,10 +3438,196 @@ mod test {
         arby_float!(f32, std::f32, f64, std::f64);
     }
 
+    #[test]
+    fn gen_from_seed_is_deterministic() {
+        let xs: Vec<u32> = Arbitrary::arbitrary(&mut Gen::from_seed(100, 42));
+        let ys: Vec<u32> = Arbitrary::arbitrary(&mut Gen::from_seed(100, 42));
+        assert_eq!(xs, ys);
+    }
+
+    #[test]
+    fn option_none_ratio() {
+        let mut g = Gen::new(5);
+        let nones =
+            (0..100_000).filter(|_| Option::<()>::arbitrary(&mut g).is_none());
+        let ratio = nones.count() as f64 / 100_000.0;
+        assert!(0.24 < ratio && ratio < 0.26, "None ratio was {}", ratio);
+
+        g.set_none_ratio(0.0);
+        assert!((0..1_000).all(|_| Option::<()>::arbitrary(&mut g).is_some()));
+        g.set_none_ratio(1.0);
+        assert!((0..1_000).all(|_| Option::<()>::arbitrary(&mut g).is_none()));
+    }
+
+    #[test]
+    fn option_non_zero_raw_ratio() {
+        use std::num::NonZeroU8;
+
+        let mut g = Gen::new(5);
+        g.set_none_ratio(1.0 / 256.0);
+        let nones = (0..256_000)
+            .filter(|_| Option::<NonZeroU8>::arbitrary(&mut g).is_none());
+        let count = nones.count();
+        assert!(800 < count && count < 1_200, "{} Nones", count);
+    }
+
+    #[test]
+    fn nested_options_reach_innermost_values() {
+        let mut g = Gen::new(5);
+        let n = 100_000;
+        let nones =
+            (0..n).filter(|_| Option::<u8>::arbitrary(&mut g).is_none());
+        let ratio = nones.count() as f64 / n as f64;
+        assert!(0.24 < ratio && ratio < 0.26, "None ratio was {}", ratio);
+
+        let innermost = (0..n).filter(|_| {
+            matches!(
+                Option::<Option<Option<u8>>>::arbitrary(&mut g),
+                Some(Some(Some(_)))
+            )
+        });
+        let ratio = innermost.count() as f64 / n as f64;
+        // Without the bias, this would be 0.75^3 = 0.42.
+        let expected = 0.75 * (1.0 - 0.25 / 2.0) * (1.0 - 0.25 / 3.0);
+        assert!((ratio - expected).abs() < 0.01, "{} != {}", ratio, expected);
+        assert_eq!(g.option_depth(), 0);
+    }
+
+    #[test]
+    fn options_inside_other_values_are_not_nested() {
+        let mut g = Gen::new(5);
+        let (mut nones, mut total) = (0, 0);
+        for _ in 0..20_000 {
+            let x = Option::<(u8, Vec<Option<u8>>)>::arbitrary(&mut g);
+            for y in x.map(|x| x.1).unwrap_or_default() {
+                nones += y.is_none() as usize;
+                total += 1;
+            }
+        }
+        let ratio = nones as f64 / total as f64;
+        assert!(0.24 < ratio && ratio < 0.26, "None ratio was {}", ratio);
+        assert_eq!(g.option_depth(), 0);
+    }
+
+    #[test]
+    fn option_depth_is_capped() {
+        let mut g = Gen::new(5);
+        {
+            let mut g = g.enter_option();
+            assert_eq!(g.option_depth(), 1);
+            assert_eq!(g.nested_none_ratio(), 0.125);
+            let mut g = g.enter_option();
+            let mut g = g.enter_option();
+            let g = g.enter_option();
+            assert_eq!(g.option_depth(), 4);
+            assert_eq!(g.nested_none_ratio(), 0.0625);
+        }
+        assert_eq!(g.option_depth(), 0);
+        assert_eq!(g.nested_none_ratio(), 0.25);
+    }
+
+    #[test]
+    fn result_err_ratio() {
+        let mut g = Gen::new(5);
+        let errs = (0..100_000)
+            .filter(|_| Result::<(), ()>::arbitrary(&mut g).is_err());
+        let ratio = errs.count() as f64 / 100_000.0;
+        assert!(0.24 < ratio && ratio < 0.26, "Err ratio was {}", ratio);
+
+        g.set_err_ratio(0.0);
+        assert!(
+            (0..1_000).all(|_| Result::<(), ()>::arbitrary(&mut g).is_ok())
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn none_ratio_out_of_range() {
+        Gen::new(5).set_none_ratio(1.5);
+    }
+
+    #[test]
+    fn cstring_shrinks_bytes_toward_one() {
+        use std::ffi::CString;
+
+        let s = CString::new("ab").unwrap();
+        let shrunk: Vec<CString> = s.shrink().collect();
+        assert!(shrunk.iter().all(|c| c.as_bytes().len() <= 2));
+        // Bytes shrink toward 1 rather than being dropped as nulls.
+        assert!(shrunk.contains(&CString::new("\x01b").unwrap()));
+        assert!(shrunk.contains(&CString::new("a\x01").unwrap()));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn size_governs_count_not_magnitude() {
+        use std::ffi::CString;
+        use std::net::{IpAddr, SocketAddr};
+        use std::num::NonZeroU32;
+        use std::time::{Duration, SystemTime};
+
+        fn size_reads<A: Arbitrary>() -> usize {
+            let mut g = Gen::new(10);
+            for _ in 0..100 {
+                A::arbitrary(&mut g);
+            }
+            g.size_reads()
+        }
+
+        assert_eq!(size_reads::<u8>(), 0);
+        assert_eq!(size_reads::<i64>(), 0);
+        assert_eq!(size_reads::<u128>(), 0);
+        assert_eq!(size_reads::<f32>(), 0);
+        assert_eq!(size_reads::<f64>(), 0);
+        assert_eq!(size_reads::<char>(), 0);
+        assert_eq!(size_reads::<bool>(), 0);
+        assert_eq!(size_reads::<NonZeroU32>(), 0);
+        assert_eq!(size_reads::<Wrapping<i16>>(), 0);
+        assert_eq!(size_reads::<IpAddr>(), 0);
+        assert_eq!(size_reads::<SocketAddr>(), 0);
+        assert_eq!(size_reads::<Duration>(), 0);
+        assert_eq!(size_reads::<SystemTime>(), 0);
+        assert_eq!(size_reads::<(u8, Option<char>)>(), 0);
+
+        assert!(size_reads::<Vec<u8>>() > 0);
+        assert!(size_reads::<String>() > 0);
+        assert!(size_reads::<CString>() > 0);
+        assert!(size_reads::<PathBuf>() > 0);
+        assert!(size_reads::<HashMap<u8, u8>>() > 0);
+        assert!(size_reads::<BTreeSet<u8>>() > 0);
+        assert!(size_reads::<VecDeque<u8>>() > 0);
+    }
+
     fn arby<A: Arbitrary>() -> A {
         Arbitrary::arbitrary(&mut Gen::new(5))
     }
 
+    #[test]
+    fn arby_try_from_errors() {
+        use std::array::TryFromSliceError;
+        use std::char::CharTryFromError;
+        use std::num::TryFromIntError;
+
+        // Each of these has exactly one value, so just check that
+        // generating them doesn't panic.
+        arby::<TryFromIntError>();
+        arby::<CharTryFromError>();
+        arby::<TryFromSliceError>();
+    }
+
+    #[test]
+    fn arby_maybe_uninit() {
+        use std::mem::MaybeUninit;
+
+        let mut g = Gen::new(5);
+        for _ in 0..100 {
+            let x = MaybeUninit::<u8>::arbitrary(&mut g);
+            assert_eq!(x.shrink().count(), 0);
+            // Generated values are always initialized.
+            let _ = unsafe { x.assume_init() };
+        }
+    }
+
     // Shrink testing.
     #[test]
     fn unit() {
@@ -1279,6 +3683,74 @@ mod test {
         );
     }
 
+    /// Repeatedly takes the first shrunk value that still fails, like
+    /// `Testable` does, and counts how often `fails` is evaluated.
+    fn minimize<A: Arbitrary>(mut x: A, fails: fn(&A) -> bool) -> (A, u64) {
+        let mut evaluations = 0;
+        'outer: loop {
+            for y in x.shrink() {
+                evaluations += 1;
+                if fails(&y) {
+                    x = y;
+                    continue 'outer;
+                }
+            }
+            return (x, evaluations);
+        }
+    }
+
+    #[test]
+    fn hash_shrinking_keeps_hasher() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{BuildHasher, Hasher};
+
+        /// A hasher whose iteration order depends on its key.
+        #[derive(Clone, Default)]
+        struct Keyed(u64);
+
+        impl BuildHasher for Keyed {
+            type Hasher = DefaultHasher;
+
+            fn build_hasher(&self) -> DefaultHasher {
+                let mut h = DefaultHasher::new();
+                h.write_u64(self.0);
+                h
+            }
+        }
+
+        let mut set = HashSet::with_hasher(Keyed(7));
+        set.extend(0..20u8);
+        assert!(set.shrink().all(|s| s.hasher().0 == 7));
+
+        // Whether the keys are iterated in order depends on the hasher, so
+        // this only keeps failing if candidates keep the original hasher.
+        fn unordered(m: &HashMap<u8, u8, Keyed>) -> bool {
+            let keys: Vec<u8> = m.keys().cloned().collect();
+            keys.windows(2).any(|w| w[0] > w[1])
+        }
+        let mut map = HashMap::with_hasher(Keyed(7));
+        map.extend((0..20u8).map(|k| (k, k)));
+        assert!(unordered(&map));
+        assert!(map.shrink().all(|m| m.hasher().0 == 7));
+        let (min, _) = minimize(map, unordered);
+        assert_eq!(min.len(), 2);
+        assert!(unordered(&min));
+        assert_eq!(min.hasher().0, 7);
+    }
+
+    #[test]
+    fn tuples_interleave_shrinking() {
+        // The failure is dominated by the second field: every candidate
+        // for the first field passes.
+        fn fails(&(ref xs, n): &(Vec<u8>, u32)) -> bool {
+            *xs == vec![7; 20] && n >= 1000
+        }
+        let (minimal, evaluations) = minimize((vec![7; 20], 1_000_000), fails);
+        assert_eq!(minimal, (vec![7; 20], 1000));
+        // Shrinking each position in turn took 1403 evaluations.
+        assert!(evaluations < 300, "took {} evaluations", evaluations);
+    }
+
     #[test]
     fn ints() {
         // TODO: Test overflow?
@@ -1322,6 +3794,48 @@ mod test {
         eq(0i128, vec![]);
     }
 
+    #[test]
+    fn large_ints_shrink_to_small_values_first() {
+        let ladder = [0, 1, 2, 3, 7, 10, 16, 100, 255, 256, 65535, 65536];
+        let shrunk: Vec<u64> = u64::MAX.shrink().take(13).collect();
+        assert_eq!(shrunk[..12], ladder);
+        assert_eq!(shrunk[12], u64::MAX - u64::MAX / 2);
+
+        let shrunk: Vec<i32> = (-100_000i32).shrink().take(13).collect();
+        assert_eq!(shrunk[0..2], [0, 100_000]);
+        let negated: Vec<i32> =
+            ladder[1..].iter().map(|&k| -(k as i32)).collect();
+        assert_eq!(shrunk[2..], negated[..]);
+
+        // Small values are only halved.
+        eq(
+            65536u32,
+            vec![
+                0, 32768, 49152, 57344, 61440, 63488, 64512, 65024, 65280,
+                65408, 65472, 65504, 65520, 65528, 65532, 65534, 65535,
+            ],
+        );
+    }
+
+    #[test]
+    fn large_int_shrinking_evaluations() {
+        fn fails(&n: &u64) -> bool {
+            n >= 1000
+        }
+        let (minimal, evaluations) = minimize(u64::MAX, fails);
+        assert_eq!(minimal, 1000);
+        // Halving alone took 143 evaluations.
+        assert!(evaluations < 70, "took {} evaluations", evaluations);
+
+        fn fails_small(&n: &u64) -> bool {
+            n >= 5
+        }
+        let (minimal, evaluations) = minimize(u64::MAX, fails_small);
+        assert_eq!(minimal, 5);
+        // Halving alone took 131 evaluations.
+        assert!(evaluations < 20, "took {} evaluations", evaluations);
+    }
+
     #[test]
     fn uints() {
         eq(5usize, vec![0, 3, 4]);
@@ -1410,6 +3924,225 @@ mod test {
         eq(Wrapping(0i32), vec![]);
     }
 
+    #[test]
+    fn wrapping_signed_min() {
+        ordered_eq(
+            Wrapping(i8::MIN),
+            [0, -64, -96, -112, -120, -124, -126, -127]
+                .iter()
+                .map(|&x| Wrapping(x))
+                .collect(),
+        );
+    }
+
+    #[test]
+    fn bounded_bounds() {
+        use crate::wrappers::BoundedBound;
+        use std::ops::Bound;
+
+        let bounds: Vec<BoundedBound<u8>> =
+            (0..1_000).map(|_| arby()).collect();
+        assert!(bounds.iter().all(|b| b.0 != Bound::Unbounded));
+        assert!(bounds.iter().any(|b| matches!(b.0, Bound::Included(_))));
+        assert!(bounds.iter().any(|b| matches!(b.0, Bound::Excluded(_))));
+        assert_eq!(
+            shrunk(BoundedBound(Bound::Excluded(2u8))),
+            set(vec![
+                BoundedBound(Bound::Excluded(0)),
+                BoundedBound(Bound::Excluded(1)),
+            ])
+        );
+    }
+
+    #[test]
+    fn choose() {
+        let mut g = Gen::new(10);
+        let empty: &[u8] = &[];
+        assert_eq!(g.try_choose(empty), None);
+        assert_eq!(g.choose(&[5]), Some(&5));
+        assert_eq!(g.choose_or_panic(&[5]), &5);
+        let chosen: HashSet<u8> =
+            (0..100).map(|_| *g.choose_or_panic(&[1, 2, 3])).collect();
+        assert_eq!(chosen, set(vec![1, 2, 3]));
+    }
+
+    /// Returns the chi-square statistic of `counts` against a uniform
+    /// distribution.
+    fn chi_square(counts: &[u64]) -> f64 {
+        let total: u64 = counts.iter().sum();
+        let expected = total as f64 / counts.len() as f64;
+        counts.iter().map(|&c| (c as f64 - expected).powi(2) / expected).sum()
+    }
+
+    #[test]
+    fn choose_index() {
+        let mut g = Gen::new(10);
+        assert_eq!(g.choose_index(0), None);
+        assert_eq!(g.choose_index(1), Some(0));
+        let mut counts = [0; 5];
+        for _ in 0..50_000 {
+            counts[g.choose_index(5).unwrap()] += 1;
+        }
+        // The 99.99th percentile for 4 degrees of freedom is about 23.5.
+        assert!(chi_square(&counts) < 23.5, "{:?}", counts);
+    }
+
+    #[test]
+    fn choose_multiple() {
+        let mut g = Gen::new(10);
+        let empty: &[u8] = &[];
+        assert!(g.choose_multiple(empty, 3).is_empty());
+        assert!(g.choose_multiple(&[1, 2], 0).is_empty());
+        assert_eq!(g.choose_multiple(&[1], 1), vec![&1]);
+        let mut all = g.choose_multiple(&[1, 2, 3], 5);
+        all.sort();
+        assert_eq!(all, vec![&1, &2, &3]);
+        // Choosing a few elements of a huge slice is cheap.
+        let huge = vec![(); usize::MAX];
+        assert_eq!(g.choose_multiple(&huge, 3).len(), 3);
+
+        // Each element is equally likely to be chosen, and to come first.
+        let (mut chosen, mut first) = ([0; 4], [0; 4]);
+        for _ in 0..20_000 {
+            let xs = g.choose_multiple(&[0, 1, 2, 3], 2);
+            assert_ne!(xs[0], xs[1]);
+            first[*xs[0]] += 1;
+            for &x in xs {
+                chosen[x] += 1;
+            }
+        }
+        // The 99.99th percentile for 3 degrees of freedom is about 21.1.
+        assert!(chi_square(&chosen) < 21.1, "{:?}", chosen);
+        assert!(chi_square(&first) < 21.1, "{:?}", first);
+    }
+
+    #[test]
+    fn choose_with_replacement() {
+        let mut g = Gen::new(10);
+        let empty: &[u8] = &[];
+        assert!(g.choose_with_replacement(empty, 3).is_empty());
+        assert!(g.choose_with_replacement(&[1, 2], 0).is_empty());
+        assert_eq!(g.choose_with_replacement(&[7], 3), vec![&7, &7, &7]);
+
+        let mut counts = [0; 3];
+        for x in g.choose_with_replacement(&[0, 1, 2], 30_000) {
+            counts[*x] += 1;
+        }
+        // The 99.99th percentile for 2 degrees of freedom is about 18.4.
+        assert!(chi_square(&counts) < 18.4, "{:?}", counts);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot choose a u8 from an empty slice")]
+    fn choose_or_panic_empty() {
+        Gen::new(10).choose_or_panic::<u8>(&[]);
+    }
+
+    #[test]
+    fn such_that() {
+        let mut g = Gen::new(100);
+        for _ in 0..100 {
+            let x = super::arbitrary_such_that(&mut g, 2_000, |x: &u8| {
+                x % 100 == 7
+            });
+            assert!(matches!(x, Some(x) if x % 100 == 7), "{:?}", x);
+        }
+        assert_eq!(g.such_that(1_000, u32::arbitrary, |_| false), None);
+        assert_eq!(g.such_that(0, u32::arbitrary, |_| true), None);
+    }
+
+    #[test]
+    fn trace_vec_generation() {
+        for _ in 0..100 {
+            let (xs, events) = super::trace_arbitrary::<Vec<u8>>(20);
+            assert_eq!(events[0].method, "size");
+            assert_eq!(events[0].value, "20");
+            assert_eq!(events[1].method, "gen_range");
+            assert_eq!(events[1].params, "0..20");
+            assert_eq!(events[1].value, xs.len().to_string());
+
+            // Each element is either a problem value or a random one, which
+            // takes a draw to decide and one to pick the value.
+            let draws = &events[2..];
+            assert_eq!(draws.len(), 2 * xs.len());
+            for (pair, x) in draws.chunks(2).zip(&xs) {
+                assert_eq!(pair[0].params, "0..10");
+                assert!(pair[1].method == "gen" || pair[1].method == "choose");
+                if pair[1].method == "gen" {
+                    assert_eq!(pair[1].value, x.to_string());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn trace_disabled() {
+        let mut g = Gen::new(20);
+        Vec::<u8>::arbitrary(&mut g);
+        assert!(g.tracer.is_none());
+        g.record("size", || panic!("draws are described without a sink"));
+    }
+
+    #[test]
+    fn negations() {
+        for seed in 0..1_000 {
+            let x: Wrapping<i8> = super::negate(&mut Gen::from_seed(10, seed));
+            let y = Wrapping::<i8>::arbitrary(&mut Gen::from_seed(10, seed));
+            assert_eq!(x, -y);
+            assert_eq!(-(-x), x);
+
+            let f: f64 = super::negate(&mut Gen::from_seed(10, seed));
+            assert!(f.is_nan() || -(-f) == f);
+
+            let b = !u8::arbitrary(&mut Gen::from_seed(10, seed));
+            assert_eq!(!!b, b);
+        }
+    }
+
+    #[test]
+    fn string_lengths_favor_edges() {
+        let mut g = Gen::new(20);
+        let lens: Vec<usize> = (0..1_000)
+            .map(|_| String::arbitrary(&mut g).chars().count())
+            .collect();
+        assert!(lens.iter().all(|&n| n <= 20));
+        assert!(lens.iter().filter(|&&n| n == 0).count() > 50);
+        assert!(lens.iter().filter(|&&n| n == 20).count() > 50);
+        assert!(lens.iter().filter(|&&n| n > 0 && n < 20).count() > 700);
+        assert_eq!(String::arbitrary(&mut Gen::new(0)), "");
+    }
+
+    #[test]
+    fn arc_strs() {
+        use std::sync::Arc;
+
+        eq(Arc::<str>::from(""), vec![]);
+        eq(
+            Arc::<str>::from("ab"),
+            "ab".to_string().shrink().map(Arc::from).collect(),
+        );
+    }
+
+    #[test]
+    fn reverses() {
+        use std::cmp::Reverse;
+
+        eq(Reverse(5i32), vec![Reverse(0), Reverse(3), Reverse(4)]);
+        eq(Reverse(0i32), vec![]);
+    }
+
+    #[test]
+    fn arrays() {
+        eq([0u8; 0], vec![]);
+        ordered_eq([0u8, 0], vec![]);
+        ordered_eq([2u8, 1], vec![[0, 1], [1, 1], [2, 0]]);
+
+        let mut g = Gen::new(100);
+        let xs: Vec<[u8; 32]> =
+            (0..10).map(|_| <[u8; 32]>::arbitrary(&mut g)).collect();
+        assert!(xs.iter().skip(1).all(|x| *x != xs[0]));
+    }
+
     #[test]
     fn vecs() {
         eq(
@@ -1507,6 +4240,29 @@ mod test {
     }
 
     list_tests!(btreesets, BTreeSet::<usize>::new(), insert);
+
+    #[test]
+    fn btreeset_shrinks_to_subsets_first() {
+        let set: BTreeSet<u8> = vec![1, 3, 5].into_iter().collect();
+        let shrunk: Vec<Vec<u8>> =
+            set.shrink().map(|s| s.into_iter().collect()).collect();
+        assert_eq!(
+            shrunk,
+            vec![
+                vec![],
+                vec![3, 5],
+                vec![1, 5],
+                vec![1, 3],
+                vec![0, 3, 5],
+                vec![0, 1, 5],
+                vec![1, 2, 5],
+                vec![0, 1, 3],
+                vec![1, 3, 4],
+            ],
+        );
+        let distinct: BTreeSet<Vec<u8>> = shrunk.iter().cloned().collect();
+        assert_eq!(distinct.len(), shrunk.len());
+    }
     list_tests!(hashsets, HashSet::<usize>::new(), insert);
     list_tests!(linkedlists, LinkedList::<usize>::new(), push_back);
     list_tests!(vecdeques, VecDeque::<usize>::new(), push_back);
@@ -1528,9 +4284,80 @@ mod test {
         }
     }
 
+    #[test]
+    fn static_strs() {
+        use super::STATIC_STRS;
+
+        let mut g = Gen::new(100);
+        let seen: HashSet<&'static str> =
+            (0..1_000).map(|_| <&'static str>::arbitrary(&mut g)).collect();
+        assert!(seen.len() > STATIC_STRS.len() / 2, "{:?}", seen);
+
+        for (i, &s) in STATIC_STRS.iter().enumerate() {
+            for t in s.shrink() {
+                let j = STATIC_STRS.iter().position(|&u| u == t).unwrap();
+                assert!(j < i && t.len() <= s.len(), "{:?} -> {:?}", s, t);
+            }
+        }
+        ordered_eq("", vec![]);
+        ordered_eq(" ", vec!["", "a"]);
+        ordered_eq("-1", vec!["", "a", " ", "\n", "\t", "0", "\r\n"]);
+        let shrunk: Vec<&str> = "not in the table".shrink().collect();
+        assert!(shrunk.len() > 10);
+        assert!(shrunk.iter().all(|t| STATIC_STRS.contains(t)));
+
+        fn log_target(target: &'static str) -> String {
+            format!("[{}]", target)
+        }
+        fn prop(target: &'static str) -> bool {
+            log_target(target).len() == target.len() + 2
+        }
+        crate::quickcheck(prop as fn(&'static str) -> bool);
+    }
+
+    #[test]
+    fn cows() {
+        use std::borrow::Cow;
+
+        ordered_eq(Cow::Borrowed(""), vec![]);
+        ordered_eq(Cow::Borrowed("hello"), vec![Cow::Borrowed("")]);
+        ordered_eq(
+            Cow::Owned::<str>("a".to_string()),
+            "a".to_string().shrink().map(Cow::Owned).collect(),
+        );
+        ordered_eq(Cow::Borrowed(&b""[..]), vec![]);
+        ordered_eq(Cow::Borrowed(&b"\n"[..]), vec![Cow::Borrowed(&b""[..])]);
+        ordered_eq(
+            Cow::Owned::<[u8]>(vec![1, 2]),
+            vec![1u8, 2].shrink().map(Cow::Owned).collect(),
+        );
+    }
+
     #[test]
     fn chars() {
         eq('\x00', vec![]);
+        eq('\x02', vec!['\x00', '\x01']);
+        let shrunk: Vec<char> = 'z'.shrink().collect();
+        assert_eq!(shrunk[..2], ['a', '\x00']);
+        assert_eq!(shrunk.iter().filter(|&&c| c == 'a').count(), 1);
+        assert_eq!('A'.shrink().next(), Some('\x00'));
+        assert_eq!('a'.shrink().next(), Some('\x00'));
+    }
+
+    #[test]
+    fn vec_chars() {
+        let shrunk: Vec<Vec<char>> = vec!['x', 'y'].shrink().collect();
+        assert!(shrunk.contains(&vec!['a', 'y']));
+        assert!(shrunk.contains(&vec!['x', 'a']));
+
+        fn prop(s: Vec<char>) -> bool {
+            !s.iter().any(|c| c.is_lowercase())
+        }
+        for _ in 0..20 {
+            let witness = crate::QuickCheck::new()
+                .run_property(prop as fn(Vec<char>) -> bool);
+            assert_eq!(witness, Some(vec!["['a']".to_string()]));
+        }
     }
 
     // All this jazz is for testing set equality on the results of a shrinker.
@@ -1564,20 +4391,134 @@ mod test {
     fn ranges() {
         ordered_eq(0..0, vec![]);
         ordered_eq(1..1, vec![0..1, 1..0]);
-        ordered_eq(3..5, vec![0..5, 2..5, 3..0, 3..3, 3..4]);
-        ordered_eq(5..3, vec![0..3, 3..3, 4..3, 5..0, 5..2]);
+        ordered_eq(3..5, vec![0..5, 3..0, 2..5, 3..3, 3..4]);
+        ordered_eq(5..3, vec![0..3, 5..0, 3..3, 5..2, 4..3]);
         ordered_eq(3.., vec![0.., 2..]);
         ordered_eq(..3, vec![..0, ..2]);
         ordered_eq(.., vec![]);
-        ordered_eq(3..=5, vec![0..=5, 2..=5, 3..=0, 3..=3, 3..=4]);
+        ordered_eq(3..=5, vec![0..=5, 3..=0, 2..=5, 3..=3, 3..=4]);
         ordered_eq(..=3, vec![..=0, ..=2]);
     }
 
+    #[test]
+    fn sorted_ranges() {
+        use crate::wrappers::SortedRange;
+        use std::ops::Range;
+
+        let mut g = Gen::new(100);
+        for _ in 0..1_000 {
+            let SortedRange(r) = SortedRange::<Range<i32>>::arbitrary(&mut g);
+            assert!(r.start <= r.end, "{:?} is inverted", r);
+        }
+        ordered_eq(
+            SortedRange(3..5),
+            vec![
+                SortedRange(0..5),
+                SortedRange(2..5),
+                SortedRange(3..3),
+                SortedRange(3..4),
+            ],
+        );
+        ordered_eq(
+            SortedRange(3..3),
+            vec![SortedRange(0..3), SortedRange(2..3)],
+        );
+    }
+
+    #[test]
+    fn non_empty_ranges() {
+        use crate::wrappers::NonEmptyRange;
+
+        fn prop(r: NonEmptyRange<u32>) -> bool {
+            let non_empty = |r: &NonEmptyRange<u32>| r.0.start < r.0.end;
+            non_empty(&r) && r.shrink().all(|s| non_empty(&s))
+        }
+        crate::quickcheck(prop as fn(NonEmptyRange<u32>) -> bool);
+
+        let mut g = Gen::new(100);
+        for _ in 0..100 {
+            let NonEmptyRange(r) = NonEmptyRange::<bool>::arbitrary(&mut g);
+            assert_eq!(r, false..true);
+        }
+        ordered_eq(
+            NonEmptyRange(3..5),
+            vec![
+                NonEmptyRange(0..5),
+                NonEmptyRange(2..5),
+                NonEmptyRange(3..4),
+            ],
+        );
+        ordered_eq(NonEmptyRange(0..1), vec![]);
+    }
+
+    #[test]
+    #[should_panic(expected = "two distinct values of ()")]
+    fn non_empty_range_of_unit() {
+        use crate::wrappers::NonEmptyRange;
+
+        NonEmptyRange::<()>::arbitrary(&mut Gen::new(100));
+    }
+
+    #[test]
+    fn sorted_inclusive_ranges() {
+        use crate::wrappers::SortedRange;
+        use std::ops::RangeInclusive;
+
+        type Sorted = SortedRange<RangeInclusive<i64>>;
+        fn prop(r: Sorted) -> bool {
+            let sorted = |r: &Sorted| r.0.start() <= r.0.end();
+            sorted(&r) && r.shrink().all(|s| sorted(&s))
+        }
+        crate::quickcheck(prop as fn(Sorted) -> bool);
+        ordered_eq(
+            SortedRange(3..=5),
+            vec![
+                SortedRange(0..=5),
+                SortedRange(2..=5),
+                SortedRange(3..=3),
+                SortedRange(3..=4),
+            ],
+        );
+    }
+
+    #[test]
+    fn pathbuf_file_name_parts() {
+        ordered_eq(
+            PathBuf::from("dir/archive.tar"),
+            vec![
+                PathBuf::new(),
+                PathBuf::from("archive"),
+                PathBuf::from("tar"),
+                PathBuf::from("archive.tar"),
+                PathBuf::from("dir"),
+            ],
+        );
+        ordered_eq(PathBuf::from("a"), vec![PathBuf::new()]);
+        ordered_eq(PathBuf::new(), vec![]);
+    }
+
+    #[test]
+    fn arby_pathbuf_diversity() {
+        let sep = std::path::MAIN_SEPARATOR.to_string();
+        let paths: Vec<PathBuf> = (0..1_000).map(|_| arby()).collect();
+        let lossy: Vec<String> =
+            paths.iter().map(|p| p.to_string_lossy().into_owned()).collect();
+        assert!(lossy.iter().any(|p| p.contains(' ')));
+        assert!(lossy.iter().any(|p| p.contains(&format!("{}...", sep))));
+        assert!(lossy.iter().any(|p| p.contains(&"a".repeat(255))));
+        assert!(lossy.iter().any(|p| p.ends_with(&sep)));
+        assert!(lossy.iter().any(|p| p.contains(&sep.repeat(2))));
+        #[cfg(unix)]
+        assert!(paths.iter().any(|p| p.to_str().is_none()));
+    }
+
     #[test]
     fn pathbuf() {
         ordered_eq(
             PathBuf::from("/home/foo//.././bar"),
             vec![
+                PathBuf::new(),
+                PathBuf::from("bar"),
                 PathBuf::from("/home/foo//.."),
                 PathBuf::from("/home/foo/../bar"),
             ],
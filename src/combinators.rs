@@ -0,0 +1,246 @@
+/*!
+Arguments whose parts depend on each other.
+
+Properties often need related arguments, like a vector and a valid index
+into it. Generating them independently means discarding most tests, and
+defining a newtype for each combination is tedious. The types in this module
+generate such pairs directly, and shrink them without breaking the
+relationship between their parts.
+
+# Example
+
+```rust
+use quickcheck::{combinators::VecAndIndex, quickcheck};
+
+fn prop(v: VecAndIndex<u8>) -> bool {
+    let mut xs = v.vec.clone();
+    let x = xs.remove(v.index);
+    xs.insert(v.index, x);
+    xs == v.vec
+}
+quickcheck(prop as fn(VecAndIndex<u8>) -> bool);
+```
+*/
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::Range;
+
+use crate::wrappers::SortedRange;
+use crate::{Arbitrary, DistributionInfo, Gen};
+
+/// A non-empty vector and an index of one of its elements.
+///
+/// Shrinking never yields an empty vector, and clamps the index to the
+/// shrunk vector.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct VecAndIndex<T> {
+    /// The vector, which is never empty.
+    pub vec: Vec<T>,
+    /// An index that is less than `vec.len()`.
+    pub index: usize,
+}
+
+impl<T: Arbitrary> Arbitrary for VecAndIndex<T> {
+    fn arbitrary(g: &mut Gen) -> VecAndIndex<T> {
+        let mut vec = Vec::<T>::arbitrary(g);
+        if vec.is_empty() {
+            vec.push(T::arbitrary(g));
+        }
+        let index = g.gen_value_range(0..vec.len());
+        VecAndIndex { vec, index }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = VecAndIndex<T>>> {
+        let index = self.index;
+        let vecs =
+            self.vec.shrink().filter(|vec| !vec.is_empty()).map(move |vec| {
+                let index = index.min(vec.len() - 1);
+                VecAndIndex { vec, index }
+            });
+        let vec = self.vec.clone();
+        let indices = self
+            .index
+            .shrink()
+            .map(move |index| VecAndIndex { vec: vec.clone(), index });
+        Box::new(vecs.chain(indices))
+    }
+}
+
+/// A non-empty map and one of its keys.
+///
+/// Shrinking only yields maps that still contain the key, so the entry for
+/// the key is never removed and the key itself is not shrunk.
+#[derive(Clone, Debug)]
+pub struct MapAndKey<K, V> {
+    /// The map, which is never empty.
+    pub map: HashMap<K, V>,
+    /// A key of `map`.
+    pub key: K,
+}
+
+impl<K: Eq + Hash, V: PartialEq> PartialEq for MapAndKey<K, V> {
+    fn eq(&self, other: &MapAndKey<K, V>) -> bool {
+        self.map == other.map && self.key == other.key
+    }
+}
+
+impl<K, V> Arbitrary for MapAndKey<K, V>
+where
+    K: Arbitrary + Eq + Hash,
+    V: Arbitrary,
+{
+    fn arbitrary(g: &mut Gen) -> MapAndKey<K, V> {
+        // The key is generated rather than chosen from the map, since the
+        // iteration order of a `HashMap` varies between runs.
+        let key = K::arbitrary(g);
+        let value = V::arbitrary(g);
+        let mut map = HashMap::<K, V>::arbitrary(g);
+        map.insert(key.clone(), value);
+        MapAndKey { map, key }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = MapAndKey<K, V>>> {
+        let key = self.key.clone();
+        Box::new(
+            self.map.shrink().filter(move |map| map.contains_key(&key)).map({
+                let key = self.key.clone();
+                move |map| MapAndKey { map, key: key.clone() }
+            }),
+        )
+    }
+}
+
+/// A vector and a range of indices within it.
+///
+/// The range is never inverted and its end is at most `vec.len()`, so
+/// `&vec[range]` never panics. The range may be empty. Shrinking clamps the
+/// range to the shrunk vector.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SliceAndRange<T> {
+    /// The vector, which may be empty.
+    pub vec: Vec<T>,
+    /// A range with `start <= end <= vec.len()`.
+    pub range: Range<usize>,
+}
+
+impl<T: Arbitrary> Arbitrary for SliceAndRange<T> {
+    fn arbitrary(g: &mut Gen) -> SliceAndRange<T> {
+        let vec = Vec::<T>::arbitrary(g);
+        let a = g.gen_value_range(0..=vec.len());
+        let b = g.gen_value_range(0..=vec.len());
+        SliceAndRange { vec, range: a.min(b)..a.max(b) }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = SliceAndRange<T>>> {
+        let Range { start, end } = self.range;
+        let vecs = self.vec.shrink().map(move |vec| {
+            let end = end.min(vec.len());
+            SliceAndRange { range: start.min(end)..end, vec }
+        });
+        let vec = self.vec.clone();
+        let ranges = SortedRange(self.range.clone()).shrink().map(
+            move |SortedRange(range)| SliceAndRange {
+                vec: vec.clone(),
+                range,
+            },
+        );
+        Box::new(vecs.chain(ranges))
+    }
+}
+
+/// Each type has no special cases of its own, and chooses the length of its
+/// collection with the size.
+impl<T: Arbitrary> DistributionInfo for VecAndIndex<T> {
+    fn respects_size() -> bool {
+        true
+    }
+}
+
+impl<K, V> DistributionInfo for MapAndKey<K, V>
+where
+    K: Arbitrary + Eq + Hash,
+    V: Arbitrary,
+{
+    fn respects_size() -> bool {
+        true
+    }
+}
+
+impl<T: Arbitrary> DistributionInfo for SliceAndRange<T> {
+    fn respects_size() -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MapAndKey, SliceAndRange, VecAndIndex};
+    use crate::{Arbitrary, Gen, QuickCheck};
+
+    /// Checks `valid` on generated values, and on every shrink candidate of
+    /// them and of their first candidates.
+    fn check_invariant<A: Arbitrary>(valid: impl Fn(&A) -> bool) {
+        let mut g = Gen::new(20);
+        for _ in 0..200 {
+            let x = A::arbitrary(&mut g);
+            assert!(valid(&x));
+            for (i, s) in x.shrink().enumerate() {
+                assert!(valid(&s));
+                if i < 5 {
+                    assert!(s.shrink().all(|s| valid(&s)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn vec_and_index() {
+        check_invariant(|v: &VecAndIndex<u8>| v.index < v.vec.len());
+    }
+
+    #[test]
+    fn map_and_key() {
+        check_invariant(|m: &MapAndKey<u8, u8>| m.map.contains_key(&m.key));
+    }
+
+    #[test]
+    fn map_and_key_is_reproducible() {
+        let key = |seed| {
+            MapAndKey::<u32, u8>::arbitrary(&mut Gen::from_seed(20, seed)).key
+        };
+        for seed in 0..20 {
+            assert_eq!(key(seed), key(seed));
+        }
+    }
+
+    #[test]
+    fn slice_and_range() {
+        check_invariant(|s: &SliceAndRange<u8>| {
+            s.range.start <= s.range.end && s.range.end <= s.vec.len()
+        });
+    }
+
+    #[test]
+    fn vec_and_index_never_discards() {
+        fn prop(v: VecAndIndex<u8>) -> bool {
+            v.vec.get(v.index).is_some()
+        }
+        let passed = QuickCheck::new()
+            .tests(1_000)
+            .max_tests(1_000)
+            .quicktest(prop as fn(VecAndIndex<u8>) -> bool);
+        assert_eq!(passed.ok(), Some(1_000));
+    }
+
+    #[test]
+    fn vec_and_index_shrinks_jointly() {
+        fn prop(v: VecAndIndex<u8>) -> bool {
+            v.vec[v.index] < 10
+        }
+        let args = QuickCheck::new()
+            .run_property(prop as fn(VecAndIndex<u8>) -> bool)
+            .unwrap();
+        assert_eq!(args, vec!["VecAndIndex { vec: [10], index: 0 }"]);
+    }
+}
@@ -0,0 +1,137 @@
+/*!
+Helpers for porting `Arbitrary` impls written for quickcheck 0.9.
+
+In quickcheck 0.9, `Gen` was a trait and `Arbitrary::arbitrary` was generic
+over it:
+
+```ignore
+impl Arbitrary for Point {
+    fn arbitrary<G: Gen>(g: &mut G) -> Point {
+        Point { x: i32::arbitrary(g), y: i32::arbitrary(g) }
+    }
+}
+```
+
+In 1.x, `Gen` is a concrete type and `arbitrary` takes a `&mut Gen`. This
+module makes it possible to port such impls mechanically:
+
+* The `Gen` trait in this module offers most of what the old trait did: a
+  `size` method, and raw randomness through the methods of `rand`'s
+  `RngCore`. It is implemented for the `Gen` type, so helper functions that
+  are generic over the old trait keep working after changing their import
+  to `quickcheck::compat::Gen`.
+* The `impl_arbitrary_compat!` macro accepts an `Arbitrary` impl whose
+  `arbitrary` method has the old signature, and turns it into an impl of the
+  current trait. The method body is kept as is.
+
+This is not perfectly source compatible. In particular, `rand` is not part
+of the public API anymore, so helpers that call methods of `rand::Rng`, such
+as `g.gen_range(low, high)`, must be rewritten to use `Arbitrary` impls or
+the raw methods of `compat::Gen`.
+
+# Example
+
+```rust
+use quickcheck::compat::Gen;
+use quickcheck::{impl_arbitrary_compat, Arbitrary};
+
+#[derive(Clone, Debug)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl_arbitrary_compat! {
+    impl Arbitrary for Point {
+        fn arbitrary<G: Gen>(g: &mut G) -> Point {
+            Point { x: i32::arbitrary(g), y: coordinate(g) }
+        }
+
+        fn shrink(&self) -> Box<dyn Iterator<Item = Point>> {
+            let y = self.y;
+            Box::new(self.x.shrink().map(move |x| Point { x, y }))
+        }
+    }
+}
+
+fn coordinate<G: Gen>(g: &mut G) -> i32 {
+    (g.next_u32() % 100) as i32
+}
+```
+*/
+
+/// The subset of quickcheck 0.9's `Gen` trait that can be offered on top of
+/// the `Gen` type.
+///
+/// The raw randomness of the old trait's `RngCore` supertrait is offered by
+/// methods of this trait instead, so that `rand` stays out of the public
+/// API.
+pub trait Gen {
+    /// Returns the size configured with this generator.
+    fn size(&self) -> usize;
+
+    /// Returns a random `u32`.
+    fn next_u32(&mut self) -> u32;
+
+    /// Returns a random `u64`.
+    fn next_u64(&mut self) -> u64;
+
+    /// Fills `dest` with random bytes.
+    fn fill_bytes(&mut self, dest: &mut [u8]);
+}
+
+impl Gen for crate::Gen {
+    fn size(&self) -> usize {
+        crate::Gen::size(self)
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        crate::Gen::next_u32(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        crate::Gen::next_u64(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        crate::Gen::fill_bytes(self, dest)
+    }
+}
+
+/// Adapts an `Arbitrary` impl written for quickcheck 0.9 to the current
+/// `Arbitrary` trait.
+///
+/// The impl must be written as `impl Arbitrary for Type { ... }`, where the
+/// `arbitrary` method has the signature
+/// `fn arbitrary<G: Gen>(g: &mut G) -> Type`. Any other methods, such as
+/// `shrink`, are copied unchanged. Impls with generic parameters are not
+/// supported.
+///
+/// In the generated impl, `g` is a `&mut quickcheck::Gen` and `G` is an
+/// alias for `quickcheck::Gen`. This means the body of `arbitrary` may pass
+/// `g` to other `Arbitrary` impls, or to functions generic over
+/// `quickcheck::compat::Gen`.
+///
+/// See the [`compat`](compat/index.html) module for an example.
+#[macro_export]
+macro_rules! impl_arbitrary_compat {
+    (
+        impl Arbitrary for $ty:ty {
+            fn arbitrary<$G:ident: $bound:path>($g:ident: &mut $arg:ty)
+                -> $ret:ty
+            $body:block
+
+            $($rest:tt)*
+        }
+    ) => {
+        impl $crate::Arbitrary for $ty {
+            fn arbitrary($g: &mut $crate::Gen) -> $ret {
+                #[allow(dead_code)]
+                type $G = $crate::Gen;
+                $body
+            }
+
+            $($rest)*
+        }
+    };
+}
@@ -14,8 +14,18 @@ new kind of witness being generated. These sorts of changes may happen in
 semver compatible releases.
 */
 
-pub use crate::arbitrary::{empty_shrinker, single_shrinker, Arbitrary, Gen};
-pub use crate::tester::{quickcheck, QuickCheck, TestResult, Testable};
+pub use crate::arbitrary::{
+    any, arbitrary_recursive_option, arbitrary_such_that, empty_shrinker,
+    negate, single_shrinker, trace_arbitrary, Arbitrary, ArcArbitrary,
+    DistributionInfo, FailureContext, Gen, OptionDepthGuard, Registered,
+    TraceEvent,
+};
+pub use crate::tester::{
+    assert_shrinks_to, assuming, find_minimal, no_panic, no_panic_result,
+    quickcheck, quickcheck_with, request_stop, set_default_gen_size,
+    set_default_tests, Assuming, CaseOutcome, Config, NoPanic, NoPanicResult,
+    QuickCheck, Runner, TestResult, Testable,
+};
 
 /// A macro for writing quickcheck tests.
 ///
@@ -26,6 +36,13 @@ pub use crate::tester::{quickcheck, QuickCheck, TestResult, Testable};
 ///
 /// Note that this macro doesn't support `mut` or patterns in parameters.
 ///
+/// A property may have a `where` clause made of bounds of the form
+/// `Type: Trait`, which is passed through to the property function.
+///
+/// If the return type of a property doesn't implement `Testable`, the
+/// compiler reports an unsatisfied `Testable` bound pointing at that return
+/// type.
+///
 /// # Example
 ///
 /// ```rust
@@ -45,7 +62,9 @@ macro_rules! quickcheck {
     {
         $(
             $(#[$m:meta])*
-            fn $fn_name:ident($($arg_name:ident : $arg_ty:ty),*) -> $ret:ty {
+            fn $fn_name:ident($($arg_name:ident : $arg_ty:ty),*) -> $ret:ty
+            $(where $($bound_ty:ty : $bound:path),+ $(,)?)?
+            {
                 $($code:tt)*
             }
         )*
@@ -56,9 +75,13 @@ macro_rules! quickcheck {
                 #[test]
                 $(#[$m])*
                 fn $fn_name() {
-                    fn prop($($arg_name: $arg_ty),*) -> $ret {
+                    fn prop($($arg_name: $arg_ty),*) -> $ret
+                    $(where $($bound_ty: $bound),+)?
+                    {
                         $($code)*
                     }
+                    fn assert_testable<T: $crate::Testable>() {}
+                    assert_testable::<$ret>();
                     $crate::quickcheck(prop as fn($($arg_ty),*) -> $ret);
                 }
             )*
@@ -87,7 +110,13 @@ macro_rules! info {
 }
 
 mod arbitrary;
+pub mod combinators;
+pub mod compat;
+pub mod pool;
+pub mod stats;
 mod tester;
+pub mod text;
+pub mod wrappers;
 
 #[cfg(test)]
 mod tests;
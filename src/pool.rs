@@ -0,0 +1,237 @@
+/*!
+Generators for harnesses that generate values on several threads.
+
+A `Gen` is `Send` and `Sync`, but generating a value needs a `&mut Gen`, so
+threads can't generate values from one generator at the same time. A
+`GenPool` gives each thread that uses it its own
+generator, all with the same size, and seeded deterministically from a base
+seed. This suits custom harnesses that generate values in parallel, for
+example from a thread pool.
+
+# Example
+
+```rust
+use std::thread;
+
+use quickcheck::{pool::GenPool, Arbitrary};
+
+let pool = GenPool::new(1234, 50);
+thread::scope(|s| {
+    for _ in 0..4 {
+        s.spawn(|| pool.with_gen(Vec::<u8>::arbitrary));
+    }
+});
+```
+*/
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+
+use crate::Gen;
+
+/// The number of pools that have been created, which is used to give each
+/// pool a distinct identifier.
+static POOLS: AtomicU64 = AtomicU64::new(0);
+
+/// The generator of a thread for a pool, and a handle that is dead once the
+/// pool is dropped.
+type Slot = (Weak<()>, Option<Gen>);
+
+thread_local! {
+    /// The generator of the current thread for each pool that it used,
+    /// keyed by the identifier of the pool. A generator is `None` while it
+    /// is lent out by `GenPool::with_gen`. Entries of dropped pools are
+    /// removed.
+    static GENS: RefCell<HashMap<u64, Slot>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Hands out a generator to each thread, seeded from a base seed.
+///
+/// Threads are numbered from `0` in the order in which they first use the
+/// pool, and thread `i` gets the generator `Gen::from_seed(size, base_seed +
+/// i)` (with wrapping addition). So as long as threads first use the pool
+/// in the same order, they get the same generators in every run.
+#[derive(Debug)]
+pub struct GenPool {
+    id: u64,
+    base_seed: u64,
+    size: usize,
+    /// The number of threads that have used this pool.
+    threads: AtomicU64,
+    /// Kept alive as long as the pool, so that threads can tell which of
+    /// their generators belong to dropped pools.
+    alive: Arc<()>,
+}
+
+impl GenPool {
+    /// Creates a pool whose generators have the given size, and are seeded
+    /// starting from `base_seed`.
+    pub fn new(base_seed: u64, size: usize) -> GenPool {
+        GenPool {
+            id: POOLS.fetch_add(1, Ordering::SeqCst),
+            base_seed,
+            size,
+            threads: AtomicU64::new(0),
+            alive: Arc::new(()),
+        }
+    }
+
+    /// Returns the generator of the thread with the given index.
+    ///
+    /// This is a fresh generator, which produces the same values that the
+    /// thread's generator in `with_gen` produces from its first use.
+    pub fn thread_gen(&self, index: u64) -> Gen {
+        Gen::from_seed(self.size, self.base_seed.wrapping_add(index))
+    }
+
+    /// Calls `f` with the generator of the current thread.
+    ///
+    /// The generator is created the first time a thread calls this method,
+    /// and is kept for later calls, so values generated by successive calls
+    /// differ.
+    ///
+    /// # Panics
+    ///
+    /// This panics if it's called from within `f` for the same pool, since
+    /// the thread's generator is already in use.
+    pub fn with_gen<R, F: FnOnce(&mut Gen) -> R>(&self, f: F) -> R {
+        let taken = GENS.with(|gens| {
+            let mut gens = gens.borrow_mut();
+            if !gens.contains_key(&self.id) {
+                // Drop the generators of pools that are gone, which other
+                // threads couldn't remove when those pools were dropped.
+                gens.retain(|_, (alive, _)| alive.strong_count() > 0);
+                let index = self.threads.fetch_add(1, Ordering::SeqCst);
+                let gen = self.thread_gen(index);
+                gens.insert(self.id, (Arc::downgrade(&self.alive), Some(gen)));
+            }
+            gens.get_mut(&self.id).unwrap().1.take()
+        });
+        let mut lent = Lent {
+            pool: self.id,
+            gen: Some(taken.expect("GenPool::with_gen called recursively")),
+        };
+        f(lent.gen.as_mut().unwrap())
+    }
+}
+
+impl Drop for GenPool {
+    fn drop(&mut self) {
+        let _ = GENS.try_with(|gens| gens.borrow_mut().remove(&self.id));
+    }
+}
+
+/// A generator lent out by `GenPool::with_gen`, which is given back when
+/// this is dropped, even if the closure panicked.
+struct Lent {
+    pool: u64,
+    gen: Option<Gen>,
+}
+
+impl Drop for Lent {
+    fn drop(&mut self) {
+        let gen = self.gen.take();
+        // The thread local may already be destroyed if this thread is
+        // exiting, in which case the generator isn't needed anymore.
+        let _ = GENS.try_with(|gens| {
+            if let Some(slot) = gens.borrow_mut().get_mut(&self.pool) {
+                slot.1 = gen;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread;
+
+    use super::{GenPool, GENS};
+    use crate::{Arbitrary, Gen};
+
+    /// Uses the pool from two threads, one after the other, and returns the
+    /// values each generated.
+    fn draw(pool: &GenPool) -> Vec<Vec<u32>> {
+        (0..2)
+            .map(|_| {
+                thread::scope(|s| {
+                    s.spawn(|| {
+                        (0..10)
+                            .map(|_| pool.with_gen(u32::arbitrary))
+                            .collect()
+                    })
+                    .join()
+                    .unwrap()
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn threads_get_deterministic_generators() {
+        let first = draw(&GenPool::new(7, 10));
+        assert_eq!(first, draw(&GenPool::new(7, 10)));
+        assert_ne!(first[0], first[1]);
+        assert_ne!(first, draw(&GenPool::new(8, 10)));
+
+        let mut g = Gen::from_seed(10, 8);
+        let expected: Vec<u32> =
+            (0..10).map(|_| u32::arbitrary(&mut g)).collect();
+        assert_eq!(first[1], expected);
+    }
+
+    #[test]
+    fn generators_have_the_pool_size() {
+        let pool = GenPool::new(0, 17);
+        assert_eq!(pool.with_gen(|g| g.size()), 17);
+        assert_eq!(pool.thread_gen(3).size(), 17);
+    }
+
+    #[test]
+    fn generator_is_kept_after_panic() {
+        let pool = GenPool::new(0, 10);
+        let first = pool.with_gen(u32::arbitrary);
+        let panicked = std::panic::catch_unwind(|| {
+            pool.with_gen(|_| panic!("oops"));
+        });
+        assert!(panicked.is_err());
+        let mut g = pool.thread_gen(0);
+        let expected: Vec<u32> =
+            (0..2).map(|_| u32::arbitrary(&mut g)).collect();
+        assert_eq!(vec![first, pool.with_gen(u32::arbitrary)], expected);
+    }
+
+    #[test]
+    fn dropped_pools_release_their_generators() {
+        thread::scope(|s| {
+            s.spawn(|| {
+                let count = || GENS.with(|gens| gens.borrow().len());
+                let pool = GenPool::new(0, 10);
+                pool.with_gen(u32::arbitrary);
+                drop(pool);
+                assert_eq!(count(), 0);
+
+                // A pool dropped on another thread leaves its generator
+                // here until this thread next uses a new pool.
+                let pool = GenPool::new(0, 10);
+                pool.with_gen(u32::arbitrary);
+                thread::scope(|s| {
+                    s.spawn(move || drop(pool));
+                });
+                assert_eq!(count(), 1);
+                let pool = GenPool::new(0, 10);
+                pool.with_gen(u32::arbitrary);
+                assert_eq!(count(), 1);
+            });
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "GenPool::with_gen called recursively")]
+    fn recursive_use_panics() {
+        let pool = GenPool::new(0, 10);
+        pool.with_gen(|_| pool.with_gen(|_| ()));
+    }
+}
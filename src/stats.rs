@@ -0,0 +1,168 @@
+/*!
+Statistics about generated values, for checking the quality of generators.
+
+A custom `Arbitrary` impl is easy to get subtly wrong, e.g., by never
+generating empty collections or by clustering values in a small range. The
+`collect` function samples a type many times and summarizes a numeric
+projection of the samples, so that tests can assert that a generator covers
+the space it is meant to.
+
+# Example
+
+```rust
+use quickcheck::{stats, Gen};
+
+let mut g = Gen::new(100);
+let lens = stats::collect(&mut g, 1_000, |xs: &Vec<u8>| xs.len() as f64);
+assert_eq!(lens.min(), 0.0);
+assert!(lens.max() > 90.0);
+assert!(lens.histogram().iter().all(|&n| n > 0));
+```
+*/
+
+use std::fmt;
+
+use crate::{Arbitrary, Gen};
+
+/// The number of buckets in the histogram of `Stats`.
+const BUCKETS: usize = 10;
+
+/// A summary of a numeric projection of generated values.
+#[derive(Clone, Debug)]
+pub struct Stats {
+    count: usize,
+    min: f64,
+    max: f64,
+    mean: f64,
+    stddev: f64,
+    histogram: Vec<usize>,
+}
+
+impl Stats {
+    fn new(values: &[f64]) -> Stats {
+        let count = values.len();
+        if count == 0 {
+            return Stats {
+                count,
+                min: f64::NAN,
+                max: f64::NAN,
+                mean: f64::NAN,
+                stddev: f64::NAN,
+                histogram: vec![0; BUCKETS],
+            };
+        }
+        let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let mean = values.iter().sum::<f64>() / count as f64;
+        let variance = values.iter().map(|x| (x - mean).powi(2)).sum::<f64>()
+            / count as f64;
+
+        let mut histogram = vec![0; BUCKETS];
+        let width = (max - min) / BUCKETS as f64;
+        for &x in values {
+            let i = if width > 0.0 { ((x - min) / width) as usize } else { 0 };
+            histogram[i.min(BUCKETS - 1)] += 1;
+        }
+        Stats { count, min, max, mean, stddev: variance.sqrt(), histogram }
+    }
+
+    /// Returns the number of values sampled.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the smallest value of the projection. This is `NaN` if no
+    /// values were sampled.
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    /// Returns the largest value of the projection. This is `NaN` if no
+    /// values were sampled.
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    /// Returns the mean of the projection. This is `NaN` if no values were
+    /// sampled.
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Returns the population standard deviation of the projection. This is
+    /// `NaN` if no values were sampled.
+    pub fn stddev(&self) -> f64 {
+        self.stddev
+    }
+
+    /// Returns the number of values that fell in each of 10 equally wide
+    /// buckets spanning `min()` to `max()`.
+    ///
+    /// If every value is the same, then they are all counted in the first
+    /// bucket.
+    pub fn histogram(&self) -> &[usize] {
+        &self.histogram
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "count {}, min {}, max {}, mean {:.3}, stddev {:.3}, \
+             histogram {:?}",
+            self.count,
+            self.min,
+            self.max,
+            self.mean,
+            self.stddev,
+            self.histogram
+        )
+    }
+}
+
+/// Generates `n` values of type `A` with `g`, and summarizes the result of
+/// `projection` on each of them.
+///
+/// The summary is also logged when the `use_logging` feature is enabled.
+pub fn collect<A, F>(g: &mut Gen, n: usize, projection: F) -> Stats
+where
+    A: Arbitrary,
+    F: Fn(&A) -> f64,
+{
+    let values: Vec<f64> =
+        (0..n).map(|_| projection(&A::arbitrary(g))).collect();
+    let stats = Stats::new(&values);
+    info!("(Generated {} values: {})", std::any::type_name::<A>(), stats);
+    stats
+}
+
+#[cfg(test)]
+mod test {
+    use super::{collect, Stats};
+    use crate::Gen;
+
+    #[test]
+    fn vec_lengths() {
+        let mut g = Gen::new(50);
+        let s = collect(&mut g, 1_000, |xs: &Vec<u8>| xs.len() as f64);
+        assert_eq!(s.count(), 1_000);
+        assert!(s.min() <= 1.0);
+        assert!(s.max() >= 45.0 && s.max() < 50.0);
+        assert!(s.mean() > 15.0 && s.mean() < 35.0);
+        assert!(s.stddev() > 5.0);
+        assert_eq!(s.histogram().iter().sum::<usize>(), 1_000);
+        assert!(s.histogram().iter().all(|&n| n > 0));
+    }
+
+    #[test]
+    fn degenerate_samples() {
+        let s = Stats::new(&[]);
+        assert_eq!(s.count(), 0);
+        assert!(s.mean().is_nan());
+
+        let s = Stats::new(&[3.0, 3.0]);
+        assert_eq!((s.min(), s.max(), s.stddev()), (3.0, 3.0, 0.0));
+        assert_eq!(s.histogram()[0], 2);
+    }
+}
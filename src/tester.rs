@@ -1,11 +1,21 @@
+use std::any::type_name;
+use std::cell::Cell;
 use std::cmp;
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::env;
-use std::fmt::Debug;
+use std::fmt::{Debug, Write as _};
+use std::fs;
+use std::io::{self, Write};
 use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
 
 use crate::{
     tester::Status::{Discard, Fail, Pass},
-    Arbitrary, Gen,
+    Arbitrary, FailureContext, Gen,
 };
 
 /// The main QuickCheck type for setting configuration and running QuickCheck.
@@ -14,10 +24,176 @@ pub struct QuickCheck {
     max_tests: u64,
     min_tests_passed: u64,
     gen: Gen,
+    persistence_file: Option<PathBuf>,
+    json_report: Option<PathBuf>,
+    report_discards: bool,
+    discarded: Vec<String>,
+    /// How often each reason for discarding a test was given in the last
+    /// run, with `None` for tests discarded without a reason.
+    discard_reasons: HashMap<Option<String>, u64>,
+    /// The settings that properties consult while a case runs.
+    settings: RunSettings,
+    /// How the size changes over a run, if it isn't constant.
+    size_schedule: Option<SizeSchedule>,
+    /// The number of case seeds drawn from `gen`.
+    cases_drawn: u64,
+    /// The number of case seeds to skip before the first case is run.
+    start_case: u64,
 }
 
+/// How the size of the generator changes over a run.
+#[derive(Clone, Copy)]
+enum SizeSchedule {
+    /// Grows linearly from the first size to the second, which is reached
+    /// at the last test. Set with `QuickCheck::size_range`.
+    Range(usize, usize),
+    /// Set with `QuickCheck::gen_size_schedule`.
+    Custom(fn(u64, u64) -> usize),
+}
+
+/// The number of times `request_stop` has been called. Each run compares
+/// this with the number at its start, so a stop requested before a run
+/// started never interrupts it.
+static STOP_REQUESTS: AtomicU64 = AtomicU64::new(0);
+
+/// Asks the failures of every run that is currently in progress to stop
+/// shrinking.
+///
+/// This only affects properties run by a `QuickCheck` configured with
+/// `print_witness_on_interrupt(true)`. Such a property stops shrinking
+/// before trying its next candidate, and fails with the smallest witness
+/// found so far.
+///
+/// quickcheck doesn't install a signal handler itself. Test harnesses that
+/// want Ctrl-C to interrupt shrinking can call this function from their
+/// own handler, for example one installed with the `ctrlc` crate.
+pub fn request_stop() {
+    STOP_REQUESTS.fetch_add(1, Ordering::SeqCst);
+}
+
+/// The settings of a `QuickCheck` that properties consult while they run,
+/// rather than while their arguments are generated.
+#[derive(Clone, Copy)]
+struct RunSettings {
+    /// Whether shrinking stops when `request_stop` is called.
+    interruptible: bool,
+    /// The value of `STOP_REQUESTS` when the run started.
+    stop_requests: u64,
+    /// How many more times a failing property is run on the same arguments
+    /// to confirm the failure.
+    confirmations: u32,
+    /// Whether a failure that isn't confirmed fails the test, rather than
+    /// discarding it.
+    fail_on_flaky: bool,
+    /// Whether failures are shrunk.
+    shrink: bool,
+    /// Whether the failure of the case has already been confirmed, because
+    /// the case is being run again to shrink it.
+    confirmed: bool,
+}
+
+impl RunSettings {
+    /// The settings of properties that aren't run by a `QuickCheck`, e.g.,
+    /// when calling `Testable::result` directly.
+    const DEFAULT: RunSettings = RunSettings {
+        interruptible: false,
+        stop_requests: 0,
+        confirmations: 0,
+        fail_on_flaky: false,
+        shrink: true,
+        confirmed: false,
+    };
+
+    /// Returns whether shrinking should stop, because `request_stop` has
+    /// been called since the run started.
+    fn stop_requested(&self) -> bool {
+        self.interruptible
+            && STOP_REQUESTS.load(Ordering::SeqCst) != self.stop_requests
+    }
+
+    /// Returns the settings of the case running on this thread.
+    fn current() -> RunSettings {
+        RUN_SETTINGS.with(Cell::get)
+    }
+
+    /// Makes these the settings of the case running on this thread, until
+    /// the returned guard is dropped.
+    fn enter(self) -> RunSettingsGuard {
+        RunSettingsGuard(RUN_SETTINGS.with(|settings| settings.replace(self)))
+    }
+}
+
+thread_local! {
+    /// The settings of the `QuickCheck` running a case on this thread. These
+    /// are kept out of `Gen`, which only configures generation.
+    static RUN_SETTINGS: Cell<RunSettings> =
+        const { Cell::new(RunSettings::DEFAULT) };
+}
+
+/// Restores the settings that were replaced by `RunSettings::enter`, even if
+/// the case panics.
+struct RunSettingsGuard(RunSettings);
+
+impl Drop for RunSettingsGuard {
+    fn drop(&mut self) {
+        let previous = self.0;
+        let _ = RUN_SETTINGS.try_with(|settings| settings.set(previous));
+    }
+}
+
+/// Defaults set with `set_default_tests` and `set_default_gen_size`.
+static DEFAULT_TESTS: Mutex<Option<u64>> = Mutex::new(None);
+static DEFAULT_GEN_SIZE: Mutex<Option<usize>> = Mutex::new(None);
+
+/// Sets `slot` to `value` unless it was already set.
+fn set_default<T: Copy>(slot: &Mutex<Option<T>>, value: T) -> Result<(), T> {
+    let mut slot = slot.lock().unwrap_or_else(|e| e.into_inner());
+    match *slot {
+        Some(_) => Err(value),
+        None => {
+            *slot = Some(value);
+            Ok(())
+        }
+    }
+}
+
+fn get_default<T: Copy>(slot: &Mutex<Option<T>>) -> Option<T> {
+    *slot.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Sets the number of tests run by `QuickCheck::new()` for the rest of the
+/// process.
+///
+/// This makes it possible to change the default for every property in a
+/// test binary at once, e.g., from a custom test harness. The
+/// `QUICKCHECK_TESTS` environment variable still takes precedence, and so
+/// does `QuickCheck::tests`.
+///
+/// The default can only be set once. If it was already set, then `value`
+/// is returned as an error.
+pub fn set_default_tests(value: u64) -> Result<(), u64> {
+    set_default(&DEFAULT_TESTS, value)
+}
+
+/// Sets the size of the generator used by `QuickCheck::new()` for the rest
+/// of the process.
+///
+/// This makes it possible to change the default for every property in a
+/// test binary at once, e.g., from a custom test harness. The
+/// `QUICKCHECK_GENERATOR_SIZE` environment variable still takes precedence,
+/// and so does `QuickCheck::gen`.
+///
+/// The default can only be set once. If it was already set, then `value`
+/// is returned as an error.
+pub fn set_default_gen_size(value: usize) -> Result<(), usize> {
+    set_default(&DEFAULT_GEN_SIZE, value)
+}
+
+/// The maximum number of discarded arguments kept by `report_discards`.
+const MAX_REPORTED_DISCARDS: usize = 10;
+
 fn qc_tests() -> u64 {
-    let default = 100;
+    let default = get_default(&DEFAULT_TESTS).unwrap_or(100);
     match env::var("QUICKCHECK_TESTS") {
         Ok(val) => val.parse().unwrap_or(default),
         Err(_) => default,
@@ -33,13 +209,25 @@ fn qc_max_tests() -> u64 {
 }
 
 fn qc_gen_size() -> usize {
-    let default = 100;
+    let default = get_default(&DEFAULT_GEN_SIZE).unwrap_or(100);
     match env::var("QUICKCHECK_GENERATOR_SIZE") {
         Ok(val) => val.parse().unwrap_or(default),
         Err(_) => default,
     }
 }
 
+fn qc_seed() -> Option<u64> {
+    env::var("QUICKCHECK_SEED").ok()?.parse().ok()
+}
+
+fn qc_start_case() -> u64 {
+    let default = 0;
+    match env::var("QUICKCHECK_START_CASE") {
+        Ok(val) => val.parse().unwrap_or(default),
+        Err(_) => default,
+    }
+}
+
 fn qc_min_tests_passed() -> u64 {
     let default = 0;
     match env::var("QUICKCHECK_MIN_TESTS_PASSED") {
@@ -48,6 +236,222 @@ fn qc_min_tests_passed() -> u64 {
     }
 }
 
+/// Returns a persistence file for the current test, if
+/// `QUICKCHECK_PERSIST_DIR` is set.
+///
+/// The file is named after the current thread, since Rust's test harness
+/// names each test's thread after the test.
+fn qc_persistence_file() -> Option<PathBuf> {
+    let dir = env::var_os("QUICKCHECK_PERSIST_DIR")?;
+    let thread = thread::current();
+    let name = match thread.name() {
+        None | Some("main") => return None,
+        Some(name) => name,
+    };
+    let file: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    Some(PathBuf::from(dir).join(file))
+}
+
+fn qc_json_report() -> Option<PathBuf> {
+    env::var_os("QUICKCHECK_JSON").map(PathBuf::from)
+}
+
+/// Reads the seeds of previously persisted failures from `path`.
+///
+/// Each line of a persistence file has the form `<seed> <case> <arguments>`.
+/// Lines that don't match this form are ignored, as is a missing file.
+fn read_persisted_seeds(path: &Path) -> Vec<u64> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(_) => return vec![],
+    };
+    contents
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split(' ').next()?.parse().ok())
+        .collect()
+}
+
+/// Appends a failure found with the given case seed to `path`, unless a
+/// failure with that seed has already been recorded.
+fn persist_failure(path: &Path, seed: u64, case: u64, result: &TestResult) {
+    if read_persisted_seeds(path).contains(&seed) {
+        return;
+    }
+    if let Err(err) = append_failure(path, seed, case, result) {
+        info!("(Failed to persist failure to {}: {})", path.display(), err);
+    }
+}
+
+fn append_failure(
+    path: &Path,
+    seed: u64,
+    case: u64,
+    result: &TestResult,
+) -> io::Result<()> {
+    let new_file = !path.exists();
+    let mut file =
+        fs::OpenOptions::new().create(true).append(true).open(path)?;
+    if new_file {
+        writeln!(
+            file,
+            "# Failures found by quickcheck. Each line is replayed before \
+             new random tests are run."
+        )?;
+    }
+    writeln!(file, "{} {} ({})", seed, case, result.arguments.join(", "))
+}
+
+/// Appends a JSON report of the failure `result` of the property `property`
+/// to `path`, as a single line.
+fn write_json_report(path: &Path, property: &str, result: &TestResult) {
+    if let Err(err) = append_json_report(path, property, result) {
+        info!("(Failed to write JSON report to {}: {})", path.display(), err);
+    }
+}
+
+fn append_json_report(
+    path: &Path,
+    property: &str,
+    result: &TestResult,
+) -> io::Result<()> {
+    let mut file =
+        fs::OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(json_report(property, result).as_bytes())
+}
+
+fn json_report(property: &str, result: &TestResult) -> String {
+    let arguments: Vec<String> =
+        result.arguments.iter().map(|arg| json_string(arg)).collect();
+    let (seed, case) = match result.reproduce {
+        None => ("null".to_string(), "null".to_string()),
+        Some((seed, case)) => {
+            (json_string(&seed.to_string()), case.to_string())
+        }
+    };
+    format!(
+        "{{\"property\": {}, \"arguments\": [{}], \"error\": {}, \
+         \"shrink_steps\": {}, \"seed\": {}, \"case\": {}}}\n",
+        json_string(property),
+        arguments.join(", "),
+        result.err.as_ref().map_or("null".to_string(), |err| json_string(err)),
+        result.shrink_steps,
+        seed,
+        case,
+    )
+}
+
+/// Returns `s` as a JSON string literal, including the quotes.
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c < ' ' => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Configuration for running QuickCheck, as an alternative to the builder
+/// methods on `QuickCheck`.
+///
+/// The default configuration is the one used by `QuickCheck::new`,
+/// including any overrides from environment variables. New fields may be
+/// added in the future, so a `Config` is created by modifying the default:
+///
+/// ```rust
+/// use quickcheck::{quickcheck_with, Config};
+///
+/// fn prop(xs: Vec<u8>) -> bool {
+///     xs.len() < 10
+/// }
+///
+/// let mut config = Config::default();
+/// config.gen_size = 10;
+/// quickcheck_with(config, prop as fn(Vec<u8>) -> bool);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Config {
+    /// The number of tests to pass. See `QuickCheck::tests`.
+    pub tests: u64,
+    /// The maximum number of tests to run. See `QuickCheck::max_tests`.
+    pub max_tests: u64,
+    /// The minimum number of tests that need to pass. See
+    /// `QuickCheck::min_tests_passed`.
+    pub min_tests_passed: u64,
+    /// The size of the generator. See `QuickCheck::with_generator_size`.
+    pub gen_size: usize,
+    /// The seed of the generator, or `None` to seed it from entropy.
+    pub seed: Option<u64>,
+    /// The number of test cases to skip before the first one is run.
+    pub start_case: u64,
+    /// The file used to persist failures. See
+    /// `QuickCheck::persistence_file`.
+    pub persistence_file: Option<PathBuf>,
+    /// The file that failures are reported to as JSON. See
+    /// `QuickCheck::json_report`.
+    pub json_report: Option<PathBuf>,
+}
+
+impl Default for Config {
+    /// Returns the default configuration, with overrides taken from the
+    /// `QUICKCHECK_TESTS`, `QUICKCHECK_MAX_TESTS`,
+    /// `QUICKCHECK_MIN_TESTS_PASSED`, `QUICKCHECK_GENERATOR_SIZE`,
+    /// `QUICKCHECK_SEED`, `QUICKCHECK_START_CASE`, `QUICKCHECK_PERSIST_DIR`
+    /// and `QUICKCHECK_JSON` environment variables.
+    fn default() -> Config {
+        let tests = qc_tests();
+        Config {
+            tests,
+            max_tests: cmp::max(tests, qc_max_tests()),
+            min_tests_passed: qc_min_tests_passed(),
+            gen_size: qc_gen_size(),
+            seed: qc_seed(),
+            start_case: qc_start_case(),
+            persistence_file: qc_persistence_file(),
+            json_report: qc_json_report(),
+        }
+    }
+}
+
+impl From<Config> for QuickCheck {
+    fn from(config: Config) -> QuickCheck {
+        let gen = match config.seed {
+            Some(seed) => Gen::from_seed(config.gen_size, seed),
+            None => Gen::new(config.gen_size),
+        };
+        QuickCheck {
+            tests: config.tests,
+            max_tests: config.max_tests,
+            min_tests_passed: config.min_tests_passed,
+            gen,
+            persistence_file: config.persistence_file,
+            json_report: config.json_report,
+            report_discards: false,
+            discarded: vec![],
+            discard_reasons: HashMap::new(),
+            settings: RunSettings::DEFAULT,
+            size_schedule: None,
+            cases_drawn: 0,
+            start_case: config.start_case,
+        }
+    }
+}
+
 impl QuickCheck {
     /// Creates a new QuickCheck value.
     ///
@@ -57,19 +461,108 @@ impl QuickCheck {
     ///
     /// By default, the maximum number of passed tests is set to `100`, the max
     /// number of overall tests is set to `10000` and the generator is created
-    /// with a size of `100`.
+    /// with a size of `100`. These defaults may be overridden with the
+    /// `QUICKCHECK_TESTS`, `QUICKCHECK_MAX_TESTS` and
+    /// `QUICKCHECK_GENERATOR_SIZE` environment variables, respectively.
+    ///
+    /// When a property fails, the panic message of `quickcheck` says how to
+    /// reproduce the failure with the `QUICKCHECK_SEED` and
+    /// `QUICKCHECK_START_CASE` environment variables. The former seeds the
+    /// generator, and the latter skips the given number of test cases
+    /// before the first one is run.
     pub fn new() -> QuickCheck {
-        let gen = Gen::new(qc_gen_size());
-        let tests = qc_tests();
-        let max_tests = cmp::max(tests, qc_max_tests());
-        let min_tests_passed = qc_min_tests_passed();
-
-        QuickCheck { tests, max_tests, min_tests_passed, gen }
+        QuickCheck::from(Config::default())
     }
 
     /// Set the random number generator to be used by QuickCheck.
-    pub fn gen(self, gen: Gen) -> QuickCheck {
-        QuickCheck { gen, ..self }
+    ///
+    /// If the generator was already used, its values can't be reproduced
+    /// from its seed anymore, so it's reseeded with a seed drawn from it.
+    /// `QuickCheck::seed` and the failure message report the new seed.
+    pub fn gen(self, mut gen: Gen) -> QuickCheck {
+        if !gen.is_fresh() {
+            let seed = gen.gen();
+            gen.reseed(seed);
+        }
+        QuickCheck { gen, cases_drawn: 0, ..self }
+    }
+
+    /// Set the size of the generator used by QuickCheck.
+    ///
+    /// The size bounds how large generated values such as vectors and
+    /// strings may be. See `Gen::new` for what exactly `size` controls.
+    ///
+    /// This is a shorthand for replacing the generator with
+    /// `Gen::new(size)`, except that the generator's random state is kept.
+    /// The default size is `100`, or the value of the
+    /// `QUICKCHECK_GENERATOR_SIZE` environment variable if it is set.
+    ///
+    /// Every case is generated with this size, even if a size range or
+    /// schedule was set with `size_range` or `gen_size_schedule`.
+    pub fn with_generator_size(mut self, size: usize) -> QuickCheck {
+        self.gen.set_size(size);
+        self.size_schedule = None;
+        self
+    }
+
+    /// Grow the size of the generator over the course of a run.
+    ///
+    /// By default, every case is generated with the same size. With a size
+    /// range, the first case is generated with size `min`, and the size
+    /// grows linearly up to `max` for the last of the `tests` cases. Cases
+    /// beyond that, which are run when tests are discarded, use `max`. This
+    /// way, failures on empty or tiny values are found first, while later
+    /// cases still reach large values.
+    ///
+    /// The size of a case only depends on its number, so setting
+    /// `QUICKCHECK_START_CASE` reproduces a failure with the size it
+    /// occurred at. Persisted failures are replayed with size `max`. The
+    /// failure message includes the size of the failing case.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `min` is greater than `max`.
+    pub fn size_range(mut self, min: usize, max: usize) -> QuickCheck {
+        assert!(min <= max, "size range {}..={} is empty", min, max);
+        self.size_schedule = Some(SizeSchedule::Range(min, max));
+        self
+    }
+
+    /// Compute the size of the generator for each case with `schedule`.
+    ///
+    /// `schedule` is called with the number of the case, starting at `0`,
+    /// and the number of tests set with `tests`. Cases beyond the number of
+    /// tests, which are run when tests are discarded, are passed as is.
+    /// Persisted failures are replayed with the size of the last case.
+    ///
+    /// `size_range(1, max)` is the linear schedule that grows the size
+    /// from `1` to `max`, like Haskell's QuickCheck does. A schedule
+    /// replaces any size range, and vice versa. Like with a size range, the
+    /// failure message includes the size of the failing case.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use quickcheck::QuickCheck;
+    ///
+    /// // Double the size every 10 cases, up to 1000.
+    /// fn doubling(case: u64, _tests: u64) -> usize {
+    ///     1usize.checked_shl((case / 10) as u32).unwrap_or(1000).min(1000)
+    /// }
+    ///
+    /// fn prop(xs: Vec<u8>) -> bool {
+    ///     xs.len() <= 1000
+    /// }
+    /// QuickCheck::new()
+    ///     .gen_size_schedule(doubling)
+    ///     .quickcheck(prop as fn(Vec<u8>) -> bool);
+    /// ```
+    pub fn gen_size_schedule(
+        mut self,
+        schedule: fn(u64, u64) -> usize,
+    ) -> QuickCheck {
+        self.size_schedule = Some(SizeSchedule::Custom(schedule));
+        self
     }
 
     /// Set the number of tests to run.
@@ -97,11 +590,160 @@ impl QuickCheck {
     ///
     /// This actually refers to the minimum number of *valid* *passed* tests
     /// that needs to pass for the property to be considered successful.
+    ///
+    /// Since testing stops after `tests` passes or `max_tests` invocations,
+    /// `quickcheck` panics without running the property if this is larger
+    /// than either of them.
     pub fn min_tests_passed(mut self, min_tests_passed: u64) -> QuickCheck {
         self.min_tests_passed = min_tests_passed;
         self
     }
 
+    /// Set the file used to persist failures across runs.
+    ///
+    /// When a property fails, the seed that generated the failing test is
+    /// appended to this file along with its (shrunk) arguments. Every seed
+    /// recorded in the file is replayed before any new random tests are
+    /// run, so that a failure found once keeps being found until it is
+    /// fixed.
+    ///
+    /// By default, if the `QUICKCHECK_PERSIST_DIR` environment variable is
+    /// set, failures are persisted to a file in that directory named after
+    /// the current test. Otherwise, failures are not persisted.
+    pub fn persistence_file<P: Into<PathBuf>>(
+        mut self,
+        path: P,
+    ) -> QuickCheck {
+        self.persistence_file = Some(path.into());
+        self
+    }
+
+    /// Set the file that `quickcheck` writes a JSON report to when a
+    /// property fails.
+    ///
+    /// This is meant for tools that annotate test failures, which would
+    /// otherwise have to parse the panic message. Each failure is appended
+    /// to the file as a JSON object on its own line, with these fields:
+    ///
+    /// * `property`: the type name of the property, e.g.,
+    ///   `"fn(alloc::string::String) -> bool"`.
+    /// * `arguments`: the `Debug` formatting of each (shrunk) argument.
+    /// * `error`: the error or panic message, or `null` if the property
+    ///   just returned a failing result.
+    /// * `shrink_steps`: the number of shrink steps taken.
+    /// * `seed` and `case`: the values of `QUICKCHECK_SEED` and
+    ///   `QUICKCHECK_START_CASE` that reproduce the failure, or `null` if
+    ///   they are not known. The seed is a string, since it may not fit in a
+    ///   double precision float.
+    ///
+    /// Since reports are appended, several tests may share one file.
+    /// `quickcheck` panics as usual after writing a report. By default, the
+    /// report is written to the path in the `QUICKCHECK_JSON` environment
+    /// variable, if it is set.
+    pub fn json_report<P: Into<PathBuf>>(mut self, path: P) -> QuickCheck {
+        self.json_report = Some(path.into());
+        self
+    }
+
+    /// Set whether to report the arguments of discarded tests.
+    ///
+    /// When enabled, the arguments of the first few discarded tests are
+    /// kept and included in the panic message of `quickcheck` when not
+    /// enough tests could be generated. This helps diagnose properties whose
+    /// precondition rarely holds for the generated values.
+    ///
+    /// Additionally, a run in which every test was discarded is considered
+    /// a failure, even if `min_tests_passed` is `0`.
+    pub fn report_discards(mut self, report_discards: bool) -> QuickCheck {
+        self.report_discards = report_discards;
+        self
+    }
+
+    /// Set whether shrinking may be interrupted with `request_stop`.
+    ///
+    /// When enabled and `request_stop` is called while a failure is being
+    /// shrunk, shrinking stops and the failure is reported with the smallest
+    /// witness found so far, along with a note that shrinking was
+    /// interrupted. This is disabled by default.
+    pub fn print_witness_on_interrupt(
+        mut self,
+        interruptible: bool,
+    ) -> QuickCheck {
+        self.settings.interruptible = interruptible;
+        self
+    }
+
+    /// Set how many more times a failing property is run on the same
+    /// arguments to confirm that it fails.
+    ///
+    /// Properties that depend on time, threads or the iteration order of
+    /// hash maps may fail only some of the time. Shrinking such a failure
+    /// produces meaningless witnesses. With confirmations, a failure is only
+    /// reported, and shrunk, if the property fails on every rerun. A shrunk
+    /// value likewise only replaces the witness if its failure is
+    /// confirmed. Failures that aren't confirmed are flaky, and the test is
+    /// discarded with the reason `"flaky failure"`, unless `fail_on_flaky`
+    /// is enabled.
+    ///
+    /// This applies to properties that are functions of their arguments.
+    /// The default is `0`, which reports every failure.
+    pub fn confirm_failures(mut self, confirmations: u32) -> QuickCheck {
+        self.settings.confirmations = confirmations;
+        self
+    }
+
+    /// Set whether a flaky failure fails the test.
+    ///
+    /// When enabled, a failure that isn't confirmed by every rerun set with
+    /// `confirm_failures` fails the test with a "flaky property" error,
+    /// without shrinking the arguments. This is disabled by default, in
+    /// which case the test is discarded and testing continues.
+    pub fn fail_on_flaky(mut self, fail_on_flaky: bool) -> QuickCheck {
+        self.settings.fail_on_flaky = fail_on_flaky;
+        self
+    }
+
+    /// Returns a fresh generator for a single test case, along with the seed
+    /// that reproduces it and the number of the case.
+    ///
+    /// Cases are numbered from `0` in the order in which they are drawn
+    /// from the generator set with `QuickCheck::gen`.
+    fn case_gen(&mut self) -> (u64, u64, Gen) {
+        let seed = self.gen.gen();
+        self.cases_drawn += 1;
+        (seed, self.cases_drawn - 1, self.gen.fork(seed))
+    }
+
+    /// Returns the size of the given case if the size grows over a run, or
+    /// `None` if every case uses the size of `gen`.
+    ///
+    /// Replayed cases, which have no number, use the largest size.
+    fn case_size(&self, case: Option<u64>) -> Option<usize> {
+        let last = self.tests.saturating_sub(1);
+        Some(match self.size_schedule? {
+            SizeSchedule::Range(min, max) => match case {
+                Some(case) if case < last => {
+                    let grown =
+                        (max - min) as u128 * case as u128 / last as u128;
+                    min + grown as usize
+                }
+                _ => max,
+            },
+            SizeSchedule::Custom(schedule) => {
+                schedule(case.unwrap_or(last), self.tests)
+            }
+        })
+    }
+
+    /// Draws and discards case seeds until the configured start case is
+    /// reached.
+    fn skip_to_start_case(&mut self) {
+        while self.cases_drawn < self.start_case {
+            let _: u64 = self.gen.gen();
+            self.cases_drawn += 1;
+        }
+    }
+
     /// Tests a property and returns the result.
     ///
     /// The result returned is either the number of tests passed or a witness
@@ -113,18 +755,155 @@ impl QuickCheck {
     where
         A: Testable,
     {
-        let mut n_tests_passed = 0;
-        for _ in 0..self.max_tests {
-            if n_tests_passed >= self.tests {
-                break;
+        let mut state = RunState::new(self, true);
+        while !state.is_finished(self) {
+            if let CaseOutcome::Failed(_) = state.step(self, &f) {
+                return Err(state.last_failure(self, &f));
             }
-            match f.result(&mut self.gen) {
-                TestResult { status: Pass, .. } => n_tests_passed += 1,
-                TestResult { status: Discard, .. } => continue,
-                r @ TestResult { status: Fail, .. } => return Err(r),
+        }
+        Ok(state.passed)
+    }
+
+    /// Tests a property and returns the (shrunk) arguments of a failure, or
+    /// `None` if it never failed.
+    ///
+    /// Each argument is formatted with its `Debug` impl. Like `quicktest`,
+    /// this never panics because of a failure, which makes it possible for
+    /// test frameworks to report failures in their own way. Properties that
+    /// panic are reported as failures. Running too few tests because of
+    /// discards is not a failure.
+    pub fn run_property<A>(&mut self, f: A) -> Option<Vec<String>>
+    where
+        A: Testable,
+    {
+        self.quicktest(f).err().map(|r| r.arguments)
+    }
+
+    /// Checks several named properties against the same generated values.
+    ///
+    /// For each test, a single value of type `A` is generated and every
+    /// property is run on it. This avoids generating an expensive value
+    /// once for every property. A test fails if any property fails, passes
+    /// if any property passes, and is discarded otherwise.
+    ///
+    /// On failure, the value is shrunk while any of the properties that
+    /// originally failed still fails, and the names of the properties that
+    /// fail on the shrunk value are included in the panic message.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use quickcheck::{QuickCheck, TestResult};
+    ///
+    /// fn sorted(xs: &Vec<u32>) -> TestResult {
+    ///     let mut ys = xs.clone();
+    ///     ys.sort();
+    ///     TestResult::from_bool(ys.windows(2).all(|w| w[0] <= w[1]))
+    /// }
+    ///
+    /// fn same_len(xs: &Vec<u32>) -> TestResult {
+    ///     let mut ys = xs.clone();
+    ///     ys.sort();
+    ///     TestResult::from_bool(ys.len() == xs.len())
+    /// }
+    ///
+    /// QuickCheck::new().check_all(vec![
+    ///     ("sorted", sorted as fn(&Vec<u32>) -> TestResult),
+    ///     ("same_len", same_len),
+    /// ]);
+    /// ```
+    pub fn check_all<A>(&mut self, props: Vec<NamedProperty<A>>)
+    where
+        A: Arbitrary + Debug,
+    {
+        self.quickcheck(Properties { props })
+    }
+
+    /// Returns how often each reason for discarding a test was given in the
+    /// last run, most frequent first.
+    ///
+    /// Reasons are given with `TestResult::discard_because`. Tests discarded
+    /// with `TestResult::discard` are counted under `None`. Replayed
+    /// failures are not counted.
+    pub fn discard_reasons(&self) -> Vec<(Option<String>, u64)> {
+        let mut reasons: Vec<(Option<String>, u64)> = self
+            .discard_reasons
+            .iter()
+            .map(|(reason, &count)| (reason.clone(), count))
+            .collect();
+        reasons.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        reasons
+    }
+
+    /// Returns the seed of the generator used by QuickCheck.
+    ///
+    /// This is the seed given to `Gen::from_seed` or set with the
+    /// `QUICKCHECK_SEED` environment variable, or the seed drawn from
+    /// entropy otherwise. It doesn't change while tests are run, so running
+    /// the same property with a generator created from this seed reproduces
+    /// the same test cases.
+    pub fn seed(&self) -> u64 {
+        self.gen.seed()
+    }
+
+    /// Returns a line naming the most frequent reasons for discarding tests
+    /// in the last run, or an empty string if no test was discarded with a
+    /// reason.
+    fn discard_reasons_msg(&self) -> String {
+        let reasons = self.discard_reasons();
+        if reasons.iter().all(|(reason, _)| reason.is_none()) {
+            return String::new();
+        }
+        let top: Vec<String> = reasons
+            .iter()
+            .take(3)
+            .map(|(reason, count)| match *reason {
+                Some(ref reason) => format!("{} ({})", reason, count),
+                None => format!("no reason ({})", count),
+            })
+            .collect();
+        format!("\nDiscard reasons: {}", top.join(", "))
+    }
+
+    /// Tests a property and returns up to `max_failures` witnesses of
+    /// failure.
+    ///
+    /// Unlike `quicktest`, testing does not stop at the first failure.
+    /// Instead, generation continues until either `max_failures` failing
+    /// results have been collected or the configured number of tests has
+    /// been exhausted. Each failure is shrunk independently.
+    ///
+    /// If the property never fails, then an empty vector is returned.
+    pub fn find_all_failures<A>(
+        &mut self,
+        f: A,
+        max_failures: usize,
+    ) -> Vec<TestResult>
+    where
+        A: Testable,
+    {
+        let mut state = RunState::new(self, false);
+        let mut failures = vec![];
+        while failures.len() < max_failures
+            && state.passed + (failures.len() as u64) < self.tests
+            && state.cases < self.max_tests
+        {
+            if let CaseOutcome::Failed(_) = state.step(self, &f) {
+                failures.push(state.last_failure(self, &f));
             }
         }
-        Ok(n_tests_passed)
+        failures
+    }
+
+    /// Returns a `Runner` that tests the property `f` one case at a time.
+    ///
+    /// This is useful for building custom test harnesses, which may want to
+    /// inspect each case as it runs and decide for themselves whether to
+    /// continue. `quicktest` is equivalent to stepping a runner until it is
+    /// finished or a case fails, and then returning the shrunk failure.
+    pub fn runner<A: Testable>(mut self, f: A) -> Runner<A> {
+        let state = RunState::new(&mut self, true);
+        Runner { qc: self, f, state }
     }
 
     /// Tests a property and calls `panic!` on failure.
@@ -160,61 +939,346 @@ impl QuickCheck {
         // Ignore log init failures, implying it has already been done.
         let _ = crate::env_logger_init();
 
+        let reachable = cmp::min(self.tests, self.max_tests);
+        if self.min_tests_passed > reachable {
+            panic!(
+                "(min_tests_passed is {}, but at most {} tests can pass with \
+                 tests = {} and max_tests = {}.)",
+                self.min_tests_passed, reachable, self.tests, self.max_tests
+            )
+        }
+
         let n_tests_passed = match self.quicktest(f) {
             Ok(n_tests_passed) => n_tests_passed,
-            Err(result) => panic!(result.failed_msg()),
+            Err(result) => {
+                if let Some(ref path) = self.json_report {
+                    write_json_report(path, type_name::<A>(), &result);
+                }
+                panic!(result.failed_msg())
+            }
         };
 
-        if n_tests_passed >= self.min_tests_passed {
-            info!("(Passed {} QuickCheck tests.)", n_tests_passed)
+        let all_discarded = n_tests_passed == 0 && !self.discarded.is_empty();
+        let reasons = self.discard_reasons_msg();
+        if n_tests_passed >= self.min_tests_passed && !all_discarded {
+            info!("(Passed {} QuickCheck tests.){}", n_tests_passed, reasons)
+        } else if self.discarded.is_empty() {
+            panic!(
+                "(Unable to generate enough tests, {} not discarded.){}",
+                n_tests_passed, reasons
+            )
         } else {
             panic!(
-                "(Unable to generate enough tests, {} not discarded.)",
-                n_tests_passed
+                "(Unable to generate enough tests, {} not discarded.){}\n\
+                 Discarded arguments:\n{}",
+                n_tests_passed,
+                reasons,
+                self.discarded.join("\n")
             )
         }
     }
 }
 
-/// Convenience function for running QuickCheck.
-///
-/// This is an alias for `QuickCheck::new().quickcheck(f)`.
-pub fn quickcheck<A: Testable>(f: A) {
-    QuickCheck::new().quickcheck(f)
+/// The outcome of running a single test case with `Runner::step`.
+#[derive(Clone, Debug)]
+pub enum CaseOutcome {
+    /// The test passed.
+    Pass,
+    /// The test was discarded.
+    Discard,
+    /// The test failed. The result holds the arguments as they were
+    /// generated, before any shrinking.
+    Failed(TestResult),
 }
 
-/// Describes the status of a single instance of a test.
+/// Tests a property one case at a time.
 ///
-/// All testable things must be capable of producing a `TestResult`.
-#[derive(Clone, Debug)]
-pub struct TestResult {
-    status: Status,
-    arguments: Vec<String>,
-    err: Option<String>,
+/// This is created with `QuickCheck::runner`, and respects the same
+/// configuration as `QuickCheck::quicktest`. In particular, any failures
+/// persisted by earlier runs are replayed by the first calls to `step`.
+/// Replayed cases don't count toward `passed` or `discarded`.
+pub struct Runner<A> {
+    qc: QuickCheck,
+    f: A,
+    state: RunState,
 }
 
-/// Whether a test has passed, failed or been discarded.
-#[derive(Clone, Debug)]
-enum Status {
-    Pass,
-    Fail,
-    Discard,
-}
+impl<A: Testable> Runner<A> {
+    /// Generates and runs a single test case.
+    ///
+    /// A failing case isn't shrunk, so the outcome holds the failure as it
+    /// was generated. Use `shrink_last_failure` to get the shrunk witness.
+    ///
+    /// This may be called even after the runner is finished, in which case
+    /// it runs another case.
+    pub fn step(&mut self) -> CaseOutcome {
+        self.state.step(&mut self.qc, &self.f)
+    }
 
-impl TestResult {
-    /// Produces a test result that indicates the current test has passed.
-    pub fn passed() -> TestResult {
-        TestResult::from_bool(true)
+    /// Shrinks the most recent failure returned by `step`, and returns the
+    /// shrunk witness.
+    ///
+    /// The failing case is shrunk by running it again with the same seed
+    /// and size, so the property must be deterministic for the witness to
+    /// match the failure. If the case doesn't fail again, the failure is
+    /// returned as it was generated. The witness is kept, so calling this
+    /// again doesn't shrink the failure again.
+    ///
+    /// If a persistence file is configured, the failure is recorded in it.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `step` has not yet returned a failure.
+    pub fn shrink_last_failure(&mut self) -> TestResult {
+        self.state.last_failure(&self.qc, &self.f)
     }
 
-    /// Produces a test result that indicates the current test has failed.
-    pub fn failed() -> TestResult {
-        TestResult::from_bool(false)
+    /// Returns the number of cases that have passed.
+    pub fn passed(&self) -> u64 {
+        self.state.passed
     }
 
-    /// Produces a test result that indicates failure from a runtime error.
-    pub fn error<S: Into<String>>(msg: S) -> TestResult {
-        let mut r = TestResult::from_bool(false);
+    /// Returns the number of cases that have been discarded.
+    pub fn discarded(&self) -> u64 {
+        self.state.discarded
+    }
+
+    /// Returns `true` if enough tests have passed, or the maximum number of
+    /// tests have been run, according to the configuration this runner was
+    /// created with.
+    pub fn is_finished(&self) -> bool {
+        self.state.is_finished(&self.qc)
+    }
+}
+
+/// The progress of testing a property.
+struct RunState {
+    /// Whether failures are replayed from and recorded to the persistence
+    /// file.
+    persist: bool,
+    /// Persisted seeds that have yet to be replayed, in reverse order.
+    replay: Vec<u64>,
+    /// The number of new (i.e., not replayed) cases that have been run.
+    cases: u64,
+    passed: u64,
+    discarded: u64,
+    last_failure: Option<Failing>,
+}
+
+/// The last failing case run by `RunState::step`.
+struct Failing {
+    seed: u64,
+    /// The case number, if the case wasn't replayed.
+    case: Option<u64>,
+    size: Option<usize>,
+    /// The result before shrinking.
+    unshrunk: TestResult,
+    /// The result after shrinking, once `RunState::last_failure` shrunk it.
+    shrunk: Option<TestResult>,
+}
+
+impl RunState {
+    fn new(qc: &mut QuickCheck, persist: bool) -> RunState {
+        qc.discarded.clear();
+        qc.discard_reasons.clear();
+        qc.skip_to_start_case();
+        qc.settings.stop_requests = STOP_REQUESTS.load(Ordering::SeqCst);
+        let mut replay = match qc.persistence_file {
+            Some(ref path) if persist => read_persisted_seeds(path),
+            _ => vec![],
+        };
+        replay.reverse();
+        RunState {
+            persist,
+            replay,
+            cases: 0,
+            passed: 0,
+            discarded: 0,
+            last_failure: None,
+        }
+    }
+
+    fn is_finished(&self, qc: &QuickCheck) -> bool {
+        self.replay.is_empty()
+            && (self.passed >= qc.tests || self.cases >= qc.max_tests)
+    }
+
+    fn step<A: Testable>(
+        &mut self,
+        qc: &mut QuickCheck,
+        f: &A,
+    ) -> CaseOutcome {
+        let (seed, case, mut gen) = match self.replay.pop() {
+            Some(seed) => (seed, None, qc.gen.fork(seed)),
+            None => {
+                let (seed, case, gen) = qc.case_gen();
+                self.cases += 1;
+                (seed, Some(case), gen)
+            }
+        };
+        let size = qc.case_size(case);
+        let settings = RunSettings { shrink: false, ..qc.settings };
+        let mut r = run_case(qc, settings, f, &mut gen, case, size);
+        match r.status {
+            Pass => {
+                if case.is_some() {
+                    self.passed += 1;
+                }
+                CaseOutcome::Pass
+            }
+            Discard => {
+                if case.is_some() {
+                    self.discarded += 1;
+                    *qc.discard_reasons.entry(r.err.take()).or_insert(0) += 1;
+                    if qc.report_discards
+                        && qc.discarded.len() < MAX_REPORTED_DISCARDS
+                    {
+                        qc.discarded
+                            .push(format!("({})", r.arguments.join(", ")));
+                    }
+                }
+                CaseOutcome::Discard
+            }
+            Fail => {
+                // Failures are only shrunk by `last_failure`, by running the
+                // case again, so that harnesses stepping through cases don't
+                // pay for shrinking failures they don't report.
+                self.last_failure = Some(Failing {
+                    seed,
+                    case,
+                    size,
+                    unshrunk: r.clone(),
+                    shrunk: None,
+                });
+                CaseOutcome::Failed(r)
+            }
+        }
+    }
+
+    /// Returns the shrunk witness of the last failure, after recording it
+    /// in the persistence file.
+    fn last_failure<A: Testable>(
+        &mut self,
+        qc: &QuickCheck,
+        f: &A,
+    ) -> TestResult {
+        let failing = self
+            .last_failure
+            .as_mut()
+            .expect("no failure to shrink; step has not failed yet");
+        if failing.shrunk.is_none() {
+            let settings = RunSettings { confirmed: true, ..qc.settings };
+            let mut gen = qc.gen.fork(failing.seed);
+            let r = run_case(
+                qc,
+                settings,
+                f,
+                &mut gen,
+                failing.case,
+                failing.size,
+            );
+            failing.shrunk = Some(if r.is_failure() {
+                r
+            } else {
+                failing.unshrunk.clone()
+            });
+        }
+        let r = failing.shrunk.clone().unwrap();
+        if let (true, Some(case), Some(ref path)) =
+            (self.persist, failing.case, &qc.persistence_file)
+        {
+            persist_failure(path, failing.seed, case, &r);
+        }
+        r
+    }
+}
+
+/// Runs the property `f` on the case generated by `gen`, with `settings`,
+/// and records how to reproduce the case in the result.
+fn run_case<A: Testable>(
+    qc: &QuickCheck,
+    settings: RunSettings,
+    f: &A,
+    gen: &mut Gen,
+    case: Option<u64>,
+    size: Option<usize>,
+) -> TestResult {
+    if let Some(size) = size {
+        gen.set_size(size);
+    }
+    let mut r = {
+        let _settings = settings.enter();
+        f.result(gen)
+    };
+    r.reproduce = case.map(|case| (qc.seed(), case));
+    r.size = size.and_then(|size| u32::try_from(size).ok());
+    r
+}
+
+/// Convenience function for running QuickCheck.
+///
+/// This is an alias for `QuickCheck::new().quickcheck(f)`.
+pub fn quickcheck<A: Testable>(f: A) {
+    QuickCheck::new().quickcheck(f)
+}
+
+/// Convenience function for running QuickCheck with the given
+/// configuration.
+///
+/// This is an alias for `QuickCheck::from(config).quickcheck(f)`.
+pub fn quickcheck_with<A: Testable>(config: Config, f: A) {
+    QuickCheck::from(config).quickcheck(f)
+}
+
+/// Describes the status of a single instance of a test.
+///
+/// All testable things must be capable of producing a `TestResult`.
+#[derive(Clone, Debug)]
+pub struct TestResult {
+    status: Status,
+    arguments: Vec<String>,
+    /// The error of a failure, or the reason a test was discarded.
+    err: Option<String>,
+    shrink_steps: u64,
+    /// The failure before it was shrunk, if it has been shrunk.
+    unshrunk: Option<Box<TestResult>>,
+    /// The seed of the generator and the number of the case that produced
+    /// this result, if known.
+    reproduce: Option<(u64, u64)>,
+    /// The size of the generator that produced this result, if the size
+    /// grew over the run. This is a `u32` to keep `TestResult` small, and
+    /// is `None` for the (absurd) sizes that don't fit.
+    size: Option<u32>,
+    /// Whether shrinking stopped early because of `request_stop`.
+    interrupted: bool,
+    /// The names of the failing properties, for failures found by
+    /// `check_all`.
+    /// This is a boxed slice to keep `TestResult` small.
+    properties: Box<[&'static str]>,
+}
+
+/// Whether a test has passed, failed or been discarded.
+#[derive(Clone, Debug)]
+enum Status {
+    Pass,
+    Fail,
+    Discard,
+}
+
+impl TestResult {
+    /// Produces a test result that indicates the current test has passed.
+    pub fn passed() -> TestResult {
+        TestResult::from_bool(true)
+    }
+
+    /// Produces a test result that indicates the current test has failed.
+    pub fn failed() -> TestResult {
+        TestResult::from_bool(false)
+    }
+
+    /// Produces a test result that indicates failure from a runtime error.
+    pub fn error<S: Into<String>>(msg: S) -> TestResult {
+        let mut r = TestResult::from_bool(false);
         r.err = Some(msg.into());
         r
     }
@@ -224,7 +1288,31 @@ impl TestResult {
     /// When a test is discarded, `quickcheck` will replace it with a
     /// fresh one (up to a certain limit).
     pub fn discard() -> TestResult {
-        TestResult { status: Discard, arguments: vec![], err: None }
+        TestResult {
+            status: Discard,
+            arguments: vec![],
+            err: None,
+            shrink_steps: 0,
+            unshrunk: None,
+            reproduce: None,
+            size: None,
+            interrupted: false,
+            properties: Box::default(),
+        }
+    }
+
+    /// Produces a test result that instructs `quickcheck` to ignore it, like
+    /// `discard`, and says why.
+    ///
+    /// `quickcheck` counts how often each reason is given, which helps to
+    /// find the precondition that rejects the most generated values. The
+    /// most frequent reasons are included in the message when not enough
+    /// tests could be generated, and are returned by
+    /// `QuickCheck::discard_reasons`.
+    pub fn discard_because<S: Into<String>>(reason: S) -> TestResult {
+        let mut r = TestResult::discard();
+        r.err = Some(reason.into());
+        r
     }
 
     /// Converts a `bool` to a `TestResult`. A `true` value indicates that
@@ -235,6 +1323,12 @@ impl TestResult {
             status: if b { Pass } else { Fail },
             arguments: vec![],
             err: None,
+            shrink_steps: 0,
+            unshrunk: None,
+            reproduce: None,
+            size: None,
+            interrupted: false,
+            properties: Box::default(),
         }
     }
 
@@ -259,24 +1353,60 @@ impl TestResult {
         }
     }
 
+    /// Returns the arguments of this test result.
+    #[cfg(test)]
+    pub(crate) fn arguments(&self) -> &[String] {
+        &self.arguments
+    }
+
     /// Returns `true` if and only if this test result describes a failing
     /// test as a result of a run time error.
     pub fn is_error(&self) -> bool {
         self.is_failure() && self.err.is_some()
     }
 
+    /// Returns the number of successful shrink steps that were taken to
+    /// reach the arguments reported by this test result.
+    ///
+    /// Each step corresponds to finding a smaller value that still witnesses
+    /// the failure. This is `0` for results that were never shrunk.
+    pub fn shrink_steps(&self) -> u64 {
+        self.shrink_steps
+    }
+
     fn failed_msg(&self) -> String {
-        match self.err {
+        let mut msg = match self.err {
             None => format!(
-                "[quickcheck] TEST FAILED. Arguments: ({})",
-                self.arguments.join(", ")
+                "[quickcheck] TEST FAILED. Arguments: ({}) \
+                 (shrunk in {} steps)",
+                self.arguments.join(", "),
+                self.shrink_steps
             ),
             Some(ref err) => format!(
                 "[quickcheck] TEST FAILED (runtime error). \
-                 Arguments: ({})\nError: {}",
+                 Arguments: ({}) (shrunk in {} steps)\nError: {}",
                 self.arguments.join(", "),
+                self.shrink_steps,
                 err
             ),
+        };
+        if !self.properties.is_empty() {
+            msg.push_str("\nFailing properties: ");
+            msg.push_str(&self.properties.join(", "));
+        }
+        if let Some(size) = self.size {
+            msg.push_str(&format!("\nGenerator size: {}", size));
+        }
+        if self.interrupted {
+            msg.push_str("\n(Shrinking was interrupted.)");
+        }
+        match self.reproduce {
+            None => msg,
+            Some((seed, case)) => format!(
+                "{}\nReproduce with QUICKCHECK_SEED={} \
+                 QUICKCHECK_START_CASE={}",
+                msg, seed, case
+            ),
         }
     }
 }
@@ -314,6 +1444,40 @@ impl Testable for TestResult {
     }
 }
 
+/// Runs a closure that generates its own values from the `Gen`.
+///
+/// This is useful when values must be generated in a correlated way, for
+/// example a map and a key that is known to be in it, without writing an
+/// `Arbitrary` newtype:
+///
+/// ```rust
+/// use quickcheck::{Arbitrary, Gen, QuickCheck, TestResult};
+///
+/// QuickCheck::new().quickcheck(|g: &mut Gen| {
+///     let xs = Vec::<u32>::arbitrary(g);
+///     if xs.is_empty() {
+///         return TestResult::discard();
+///     }
+///     let x = *g.choose(&xs).unwrap();
+///     TestResult::from_bool(xs.contains(&x))
+/// });
+/// ```
+///
+/// Since quickcheck never sees the generated values, they aren't shrunk and
+/// aren't included in the failure message. Failures can still be reproduced
+/// from their seed.
+impl<F> Testable for F
+where
+    F: Fn(&mut Gen) -> TestResult + 'static,
+{
+    fn result(&self, g: &mut Gen) -> TestResult {
+        match safe(|| self(g)) {
+            Ok(r) => r,
+            Err(err) => TestResult::error(err),
+        }
+    }
+}
+
 impl<A, E> Testable for Result<A, E>
 where
     A: Testable,
@@ -332,54 +1496,439 @@ fn debug_reprs(args: &[&dyn Debug]) -> Vec<String> {
     args.iter().map(|x| format!("{:?}", x)).collect()
 }
 
+/// The arguments of a property, as a tuple.
+trait Arguments: Arbitrary {
+    /// Return the debug formatting of each argument.
+    fn debug_reprs(&self) -> Vec<String>;
+}
+
+/// The number of shrunk values of each failing value that the first pass
+/// of `shrink_failure` tries. Every later pass tries twice as many.
+const FIRST_SHRINK_DEPTH: usize = 8;
+
+/// A failure that has been shrunk by `shrink_failure`.
+struct Shrunk<A, R> {
+    /// The smallest failing value that was found.
+    value: A,
+    /// The failure of `value`.
+    failure: R,
+    /// The number of times a shrunk value replaced the failing value.
+    steps: u64,
+    /// Whether shrinking stopped early because of `request_stop`.
+    interrupted: bool,
+}
+
+/// A failure that `shrink_failure` passes to shrinkers as the context of a
+/// failing value.
+trait Failure {
+    /// Returns the error or panic message of the failure, if any.
+    fn err(&self) -> Option<String>;
+}
+
+impl Failure for TestResult {
+    fn err(&self) -> Option<String> {
+        self.err.clone()
+    }
+}
+
+impl<T> Failure for (TestResult, T) {
+    fn err(&self) -> Option<String> {
+        self.0.err.clone()
+    }
+}
+
+impl Failure for () {
+    fn err(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Shrinks the failing value `value`, whose failure is `failure`, for as
+/// long as one of its shrunk values fails, too.
+///
+/// `fails` is given a shrunk value and the failure of the value it was
+/// shrunk from, and returns the failure of the shrunk value, or `None` if
+/// it doesn't fail. This is the shrink loop of every property that
+/// `QuickCheck` runs, as well as of `find_minimal`.
+///
+/// Shrinking runs in passes of increasing depth. A pass only tries the
+/// first few shrunk values of each failing value, so cheap structural
+/// shrinks are found before later candidates are built. When none of them
+/// fails, the next pass goes deeper, skipping the candidates that already
+/// passed. Since candidates are always tried in order, the first failing
+/// one is shrunk next, and shrinking ends at the same value as a single
+/// unlimited pass: the first value that has no failing shrunk value.
+fn shrink_failure<A, R, F>(
+    value: A,
+    failure: R,
+    settings: RunSettings,
+    mut fails: F,
+) -> Shrunk<A, R>
+where
+    A: Arbitrary,
+    R: Failure,
+    F: FnMut(&A, &R) -> Option<R>,
+{
+    let mut shrunk = Shrunk { value, failure, steps: 0, interrupted: false };
+    let mut depth = FIRST_SHRINK_DEPTH;
+    // The number of shrunk values of the current value that passed.
+    let mut passed = 0;
+    'shrink: loop {
+        let ctx = FailureContext::new(shrunk.failure.err());
+        let candidates = shrunk.value.shrink_with_context(&ctx);
+        for t in candidates.skip(passed).take(depth - passed) {
+            if settings.stop_requested() {
+                shrunk.interrupted = true;
+                break 'shrink;
+            }
+            if let Some(failure) = fails(&t, &shrunk.failure) {
+                // The shrunk value *does* witness a failure, so keep trying
+                // to shrink it.
+                shrunk.value = t;
+                shrunk.failure = failure;
+                shrunk.steps += 1;
+                passed = 0;
+                continue 'shrink;
+            }
+            passed += 1;
+        }
+        if passed < depth {
+            // If we couldn't witness a failure on any shrunk value, then the
+            // failure we already have is the smallest.
+            break;
+        }
+        depth *= 2;
+    }
+    shrunk
+}
+
+/// Reruns the property `f` on `a` as many times as failures are confirmed,
+/// and returns how many of the runs failed.
+fn reruns_failed<A, T, F>(
+    g: &mut Gen,
+    settings: RunSettings,
+    f: F,
+    a: &A,
+) -> u32
+where
+    A: Arguments,
+    T: Testable,
+    F: Fn(A) -> T + Copy + 'static,
+{
+    let mut failed = 0;
+    for _ in 0..settings.confirmations {
+        let args = a.clone();
+        if safe(move || f(args)).result(g).is_failure() {
+            failed += 1;
+        }
+    }
+    failed
+}
+
+/// Runs the property `f` on random arguments, shrinking them on failure.
+fn run_fn_property<A, T, F>(g: &mut Gen, f: F) -> TestResult
+where
+    A: Arguments,
+    T: Testable,
+    F: Fn(A) -> T + Copy + 'static,
+{
+    // The arguments are cloned once for each call of `f`, and are only
+    // formatted for the results that are reported.
+    let settings = RunSettings::current();
+    let a: A = Arbitrary::arbitrary(g);
+    let args = a.clone();
+    let mut r = safe(move || f(args)).result(g);
+    if r.is_failure() && !settings.confirmed {
+        let failed = reruns_failed(g, settings, f, &a);
+        if failed < settings.confirmations {
+            let mut r = if settings.fail_on_flaky {
+                TestResult::error(format!(
+                    "flaky property: failed {} of {} runs",
+                    failed + 1,
+                    settings.confirmations + 1
+                ))
+            } else {
+                TestResult::discard_because("flaky failure")
+            };
+            r.arguments = a.debug_reprs();
+            return r;
+        }
+    }
+    match r.status {
+        Pass => r,
+        Fail if settings.shrink => {
+            let mut unshrunk = r.clone();
+            unshrunk.arguments = a.debug_reprs();
+            let shrunk = shrink_failure(a, r, settings, |t, _| {
+                let args = t.clone();
+                let r = safe(move || f(args)).result(g);
+                // A shrunk value only replaces the witness if its failure
+                // is confirmed, too.
+                let confirmed = r.is_failure()
+                    && reruns_failed(g, settings, f, t)
+                        == settings.confirmations;
+                confirmed.then_some(r)
+            });
+            let mut r = shrunk.failure;
+            r.arguments = shrunk.value.debug_reprs();
+            r.shrink_steps = unshrunk.shrink_steps + shrunk.steps;
+            r.interrupted = shrunk.interrupted;
+            r.unshrunk = Some(Box::new(unshrunk));
+            r
+        }
+        Fail | Discard => {
+            r.arguments = a.debug_reprs();
+            r
+        }
+    }
+}
+
+/// A property and its name, as given to `QuickCheck::check_all`.
+type NamedProperty<A> = (&'static str, fn(&A) -> TestResult);
+
+/// Named properties that are checked against the same generated value.
+///
+/// This is created by `QuickCheck::check_all`.
+struct Properties<A> {
+    props: Vec<NamedProperty<A>>,
+}
+
+impl<A: Arbitrary + Debug> Properties<A> {
+    /// Runs the properties at `indices` on `a`, and returns the result of
+    /// the first one that fails along with the indices of all that fail.
+    ///
+    /// If none fail, then the result is a pass if any passed, and a discard
+    /// otherwise.
+    fn run(&self, a: &A, indices: &[usize]) -> (TestResult, Vec<usize>) {
+        let mut failing = vec![];
+        let mut first_failure = None;
+        let mut any_passed = false;
+        for &i in indices {
+            let (prop, a) = (self.props[i].1, a.clone());
+            let r = match safe(move || prop(&a)) {
+                Ok(r) => r,
+                Err(err) => TestResult::error(err),
+            };
+            match r.status {
+                Fail => {
+                    failing.push(i);
+                    first_failure.get_or_insert(r);
+                }
+                Pass => any_passed = true,
+                Discard => {}
+            }
+        }
+        let r = match first_failure {
+            Some(r) => r,
+            None if any_passed => TestResult::passed(),
+            None => TestResult::discard(),
+        };
+        (r, failing)
+    }
+}
+
+impl<A: Arbitrary + Debug> Testable for Properties<A> {
+    fn result(&self, g: &mut Gen) -> TestResult {
+        let mut a = A::arbitrary(g);
+        let all: Vec<usize> = (0..self.props.len()).collect();
+        let (mut r, mut failing) = self.run(&a, &all);
+        let settings = RunSettings::current();
+        if r.is_failure() && settings.shrink {
+            let mut unshrunk = r.clone();
+            unshrunk.arguments = vec![format!("{:?}", a)];
+            unshrunk.properties =
+                failing.iter().map(|&i| self.props[i].0).collect();
+            let shrunk = shrink_failure(
+                a,
+                (r, failing),
+                settings,
+                |t, (_, failing)| {
+                    let (r, failing) = self.run(t, failing);
+                    r.is_failure().then_some((r, failing))
+                },
+            );
+            a = shrunk.value;
+            (r, failing) = shrunk.failure;
+            r.shrink_steps = unshrunk.shrink_steps + shrunk.steps;
+            r.interrupted = shrunk.interrupted;
+            r.unshrunk = Some(Box::new(unshrunk));
+        }
+        if !matches!(r.status, Pass) {
+            r.arguments = vec![format!("{:?}", a)];
+        }
+        r.properties = failing.iter().map(|&i| self.props[i].0).collect();
+        r
+    }
+}
+
+/// A property that passes as long as the wrapped function doesn't panic.
+///
+/// This is created with `no_panic`.
+#[derive(Clone, Copy, Debug)]
+pub struct NoPanic<F>(F);
+
+/// Returns a property that passes whenever `f` returns without panicking,
+/// regardless of the value it returns.
+///
+/// A panic is reported as a failure whose error includes the panic message,
+/// and its arguments are shrunk just like those of any other property.
+///
+/// # Example
+///
+/// ```rust
+/// use quickcheck::{no_panic, quickcheck};
+///
+/// fn checked_halve(n: u32) -> u32 {
+///     n / 2
+/// }
+/// quickcheck(no_panic(checked_halve as fn(u32) -> u32));
+/// ```
+pub fn no_panic<F>(f: F) -> NoPanic<F>
+where
+    NoPanic<F>: Testable,
+{
+    NoPanic(f)
+}
+
+/// A property that passes as long as the wrapped function doesn't panic,
+/// and discards tests for which it returns an error.
+///
+/// This is created with `no_panic_result`.
+#[derive(Clone, Copy, Debug)]
+pub struct NoPanicResult<F>(F);
+
+/// Returns a property that passes whenever `f` returns `Ok` without
+/// panicking.
+///
+/// This is like `no_panic`, except that tests for which `f` returns `Err`
+/// are discarded. This suits fallible functions, which are expected to
+/// reject some inputs but should never panic on them.
+pub fn no_panic_result<F>(f: F) -> NoPanicResult<F>
+where
+    NoPanicResult<F>: Testable,
+{
+    NoPanicResult(f)
+}
+
+/// A property that is only tested on arguments satisfying a precondition.
+///
+/// This is created with `assuming`.
+#[derive(Clone, Copy, Debug)]
+pub struct Assuming<P, F> {
+    pred: P,
+    prop: F,
+}
+
+/// Returns a property that discards tests whose arguments don't satisfy
+/// `pred`, and tests `prop` on all others.
+///
+/// This is like Haskell QuickCheck's `==>`. It saves adding a
+/// `TestResult::discard_because` to the property itself when the
+/// precondition only depends on its arguments. The predicate takes each
+/// argument by reference. Tests are discarded with the reason
+/// `"assumption does not hold"`, and shrinking skips arguments that don't
+/// satisfy the predicate.
+///
+/// # Example
+///
+/// ```rust
+/// use quickcheck::{assuming, quickcheck};
+///
+/// fn is_even(n: &u32) -> bool {
+///     n % 2 == 0
+/// }
+///
+/// fn halving_is_exact(n: u32) -> bool {
+///     (n / 2) * 2 == n
+/// }
+/// quickcheck(assuming(
+///     is_even as fn(&u32) -> bool,
+///     halving_is_exact as fn(u32) -> bool,
+/// ));
+/// ```
+pub fn assuming<P, F>(pred: P, prop: F) -> Assuming<P, F>
+where
+    Assuming<P, F>: Testable,
+{
+    Assuming { pred, prop }
+}
+
+/// The outcome of calling the property of an `Assuming`.
+enum Assumed<T> {
+    Holds(T),
+    DoesNotHold,
+}
+
+impl<T: Testable> Testable for Assumed<T> {
+    fn result(&self, g: &mut Gen) -> TestResult {
+        match *self {
+            Assumed::Holds(ref r) => r.result(g),
+            Assumed::DoesNotHold => {
+                TestResult::discard_because("assumption does not hold")
+            }
+        }
+    }
+}
+
 macro_rules! testable_fn {
     ($($name: ident),*) => {
 
+impl<$($name: Arbitrary + Debug),*> Arguments for ($($name,)*) {
+    #[allow(non_snake_case)]
+    fn debug_reprs(&self) -> Vec<String> {
+        let ($(ref $name,)*) = *self;
+        debug_reprs(&[$($name),*])
+    }
+}
+
 impl<T: Testable,
      $($name: Arbitrary + Debug),*> Testable for fn($($name),*) -> T {
     #[allow(non_snake_case)]
     fn result(&self, g: &mut Gen) -> TestResult {
-        fn shrink_failure<T: Testable, $($name: Arbitrary + Debug),*>(
-            g: &mut Gen,
-            self_: fn($($name),*) -> T,
-            a: ($($name,)*),
-        ) -> Option<TestResult> {
-            for t in a.shrink() {
-                let ($($name,)*) = t.clone();
-                let mut r_new = safe(move || {self_($($name),*)}).result(g);
-                if r_new.is_failure() {
-                    {
-                        let ($(ref $name,)*) : ($($name,)*) = t;
-                        r_new.arguments = debug_reprs(&[$($name),*]);
-                    }
+        let self_ = *self;
+        run_fn_property(g, move |($($name,)*)| self_($($name),*))
+    }
+}
 
-                    // The shrunk value *does* witness a failure, so keep
-                    // trying to shrink it.
-                    let shrunk = shrink_failure(g, self_, t);
+impl<T: 'static,
+     $($name: Arbitrary + Debug),*> Testable for NoPanic<fn($($name),*) -> T> {
+    #[allow(non_snake_case)]
+    fn result(&self, g: &mut Gen) -> TestResult {
+        let f = self.0;
+        run_fn_property(g, move |($($name,)*)| {
+            let _ = f($($name),*);
+        })
+    }
+}
 
-                    // If we couldn't witness a failure on any shrunk value,
-                    // then return the failure we already have.
-                    return Some(shrunk.unwrap_or(r_new))
-                }
+impl<T: Testable,
+     $($name: Arbitrary + Debug),*> Testable
+    for Assuming<fn($(&$name),*) -> bool, fn($($name),*) -> T>
+{
+    #[allow(non_snake_case)]
+    fn result(&self, g: &mut Gen) -> TestResult {
+        let (pred, prop) = (self.pred, self.prop);
+        run_fn_property(g, move |($($name,)*)| {
+            if pred($(&$name),*) {
+                Assumed::Holds(prop($($name),*))
+            } else {
+                Assumed::DoesNotHold
             }
-            None
-        }
-
-        let self_ = *self;
-        let a: ($($name,)*) = Arbitrary::arbitrary(g);
-        let ( $($name,)* ) = a.clone();
-        let mut r = safe(move || {self_($($name),*)}).result(g);
+        })
+    }
+}
 
-        {
-            let ( $(ref $name,)* ) = a;
-            r.arguments = debug_reprs(&[$($name),*]);
-        }
-        match r.status {
-            Pass|Discard => r,
-            Fail => {
-                shrink_failure(g, self_, a).unwrap_or(r)
-            }
-        }
+impl<T: 'static, Er: 'static,
+     $($name: Arbitrary + Debug),*> Testable
+    for NoPanicResult<fn($($name),*) -> Result<T, Er>>
+{
+    #[allow(non_snake_case)]
+    fn result(&self, g: &mut Gen) -> TestResult {
+        let f = self.0;
+        run_fn_property(g, move |($($name,)*)| match f($($name),*) {
+            Ok(_) => TestResult::passed(),
+            Err(_) => TestResult::discard(),
+        })
     }
 }}}
 
@@ -393,11 +1942,91 @@ testable_fn!(A, B, C, D, E, F);
 testable_fn!(A, B, C, D, E, F, G);
 testable_fn!(A, B, C, D, E, F, G, H);
 
+/// Shrinks `start` for as long as a shrunk value satisfies `fails`, and
+/// returns the smallest value found.
+///
+/// This is the loop QuickCheck uses to shrink the arguments of a failing
+/// property, with `fails` in place of the property. Candidates are tried in
+/// the same order, and the first one that satisfies `fails` is shrunk next,
+/// until no candidate satisfies it. It's useful for testing that a custom
+/// `Arbitrary` impl shrinks to the expected values. `start` itself is
+/// assumed to satisfy `fails`, and is returned if no candidate does.
+///
+/// # Example
+///
+/// ```rust
+/// let minimal = quickcheck::find_minimal(vec![5u8, 7, 0, 9], |xs| {
+///     xs.iter().any(|&x| x >= 3)
+/// });
+/// assert_eq!(minimal, vec![3]);
+/// ```
+pub fn find_minimal<A, F>(start: A, fails: F) -> A
+where
+    A: Arbitrary,
+    F: Fn(&A) -> bool,
+{
+    shrink_failure(start, (), RunSettings::DEFAULT, |t, _| {
+        fails(t).then_some(())
+    })
+    .value
+}
+
+/// Asserts that shrinking `start` with `find_minimal` gives `expected`.
+///
+/// If it doesn't, the panic message shows every value that shrinking went
+/// through, which shows where the shrinker went wrong.
+///
+/// # Panics
+///
+/// This panics if `start` doesn't satisfy `fails`, or if the minimal value
+/// isn't equal to `expected`.
+///
+/// # Example
+///
+/// ```rust
+/// quickcheck::assert_shrinks_to(1_000u32, |&n| n >= 100, 100);
+/// ```
+pub fn assert_shrinks_to<A, F>(start: A, fails: F, expected: A)
+where
+    A: Arbitrary + Debug + PartialEq,
+    F: Fn(&A) -> bool,
+{
+    assert!(fails(&start), "{:?} doesn't fail", start);
+    let path = shrink_path(start, fails);
+    let minimal = path.last().unwrap();
+    if *minimal != expected {
+        let steps: Vec<String> =
+            path.iter().map(|a| format!("{:?}", a)).collect();
+        panic!(
+            "shrunk to {:?} instead of {:?}, via:\n{}",
+            minimal,
+            expected,
+            steps.join("\n")
+        );
+    }
+}
+
+/// Returns `start` followed by every value that `find_minimal` shrinks it
+/// to, ending with the minimal one.
+fn shrink_path<A, F>(start: A, fails: F) -> Vec<A>
+where
+    A: Arbitrary,
+    F: Fn(&A) -> bool,
+{
+    let mut path = vec![start.clone()];
+    shrink_failure(start, (), RunSettings::DEFAULT, |t, _| {
+        let failed = fails(t);
+        if failed {
+            path.push(t.clone());
+        }
+        failed.then_some(())
+    });
+    path
+}
+
 fn safe<T, F>(fun: F) -> Result<T, String>
 where
     F: FnOnce() -> T,
-    F: 'static,
-    T: 'static,
 {
     panic::catch_unwind(panic::AssertUnwindSafe(fun)).map_err(|any_err| {
         // Extract common types of panic payload:
@@ -418,7 +2047,12 @@ impl<A: Arbitrary + Debug> AShow for A {}
 
 #[cfg(test)]
 mod test {
-    use crate::{Gen, QuickCheck};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::{
+        assert_shrinks_to, find_minimal, request_stop, Arbitrary, CaseOutcome,
+        Config, Gen, QuickCheck,
+    };
 
     #[test]
     fn shrinking_regression_issue_126() {
@@ -430,6 +2064,632 @@ mod test {
             .unwrap_err();
         let expected_argument = format!("{:?}", [true, true]);
         assert_eq!(failing_case.arguments, vec![expected_argument]);
+        assert_shrinks_to(
+            vec![false, true, false, true, true, false],
+            |vals: &Vec<bool>| !thetest(vals.clone()),
+            vec![true, true],
+        );
+    }
+
+    #[test]
+    fn shrink_steps_are_counted() {
+        fn thetest(x: u32) -> bool {
+            x < 100
+        }
+        let failing_case = QuickCheck::new()
+            .quicktest(thetest as fn(x: u32) -> bool)
+            .unwrap_err();
+        assert_eq!(failing_case.arguments, vec!["100".to_string()]);
+        assert!(failing_case.shrink_steps() > 0);
+        assert_shrinks_to(u32::MAX, |&x| !thetest(x), 100);
+        assert!(failing_case.failed_msg().contains(&format!(
+            "(shrunk in {} steps)",
+            failing_case.shrink_steps()
+        )));
+    }
+
+    #[test]
+    fn find_minimal_shrinks_to_boundary() {
+        fn sum(xs: &[u8]) -> u32 {
+            xs.iter().map(|&x| u32::from(x)).sum()
+        }
+        fn prop(xs: Vec<u8>) -> bool {
+            sum(&xs) < 1_000
+        }
+        let mut g = Gen::from_seed(100, 3);
+        let mut checked = 0;
+        while checked < 10 {
+            let xs: Vec<u8> = crate::any(&mut g);
+            if prop(xs.clone()) {
+                continue;
+            }
+            checked += 1;
+            let minimal = find_minimal(xs.clone(), |xs| !prop(xs.clone()));
+            assert_eq!(sum(&minimal), 1_000, "{:?}", minimal);
+        }
+    }
+
+    #[test]
+    fn find_minimal_descends_into_first_failing_candidate() {
+        fn descend(mut xs: Vec<u8>, fails: fn(&Vec<u8>) -> bool) -> Vec<u8> {
+            while let Some(ys) = xs.shrink().find(fails) {
+                xs = ys;
+            }
+            xs
+        }
+        let predicates: [fn(&Vec<u8>) -> bool; 3] = [
+            |xs| xs.iter().filter(|&&x| x % 2 == 1).count() >= 2,
+            |xs| xs.iter().map(|&x| u32::from(x)).sum::<u32>() % 7 == 3,
+            |xs| xs.windows(2).any(|w| w[0] > w[1]),
+        ];
+        let mut g = Gen::from_seed(100, 5);
+        for &fails in &predicates {
+            let mut checked = 0;
+            while checked < 10 {
+                let xs: Vec<u8> = crate::any(&mut g);
+                if !fails(&xs) {
+                    continue;
+                }
+                checked += 1;
+                assert_eq!(
+                    find_minimal(xs.clone(), fails),
+                    descend(xs, fails)
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "shrunk to 100 instead of 99, via:\n1000\n")]
+    fn assert_shrinks_to_shows_path() {
+        assert_shrinks_to(1_000u32, |&x| x >= 100, 99);
+    }
+
+    #[test]
+    fn confirm_failures_detects_flaky_properties() {
+        use std::cell::Cell;
+
+        thread_local! {
+            static CALLS: Cell<u64> = const { Cell::new(0) };
+        }
+
+        // Fails on every other call, regardless of its argument.
+        fn flaky(_: u8) -> bool {
+            CALLS.with(|calls| {
+                calls.set(calls.get() + 1);
+                calls.get() % 2 == 0
+            })
+        }
+
+        let f = flaky as fn(u8) -> bool;
+        assert!(QuickCheck::new().quicktest(f).is_err());
+
+        let mut qc = QuickCheck::new().confirm_failures(2);
+        qc.quicktest(f).unwrap();
+        let reasons = qc.discard_reasons();
+        assert_eq!(reasons.len(), 1);
+        assert_eq!(reasons[0].0.as_deref(), Some("flaky failure"));
+
+        let r = QuickCheck::new()
+            .confirm_failures(2)
+            .fail_on_flaky(true)
+            .quicktest(f)
+            .unwrap_err();
+        assert!(r.is_error());
+        assert_eq!(r.shrink_steps(), 0);
+        let msg = r.failed_msg();
+        assert!(msg.contains("flaky property: failed 2 of 3 runs"), "{}", msg);
+    }
+
+    #[test]
+    fn confirm_failures_shrinks_deterministic_failures() {
+        fn prop(xs: Vec<u8>) -> bool {
+            xs.len() < 3
+        }
+        let witness = QuickCheck::new()
+            .confirm_failures(3)
+            .fail_on_flaky(true)
+            .run_property(prop as fn(Vec<u8>) -> bool);
+        assert_eq!(witness, Some(vec!["[0, 0, 0]".to_string()]));
+    }
+
+    #[test]
+    fn confirm_failures_runs_each_failure_once() {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        fn prop(_: ()) -> bool {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            false
+        }
+        QuickCheck::new()
+            .confirm_failures(3)
+            .quicktest(prop as fn(()) -> bool)
+            .unwrap_err();
+        // The failing call and its 3 confirmations, and the call that runs
+        // the case again to shrink it, whose failure isn't confirmed again.
+        // `()` doesn't shrink.
+        assert_eq!(CALLS.load(Ordering::SeqCst), 5);
+    }
+
+    #[test]
+    fn shrinking_passes_reach_minimal_witness() {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        fn prop(xs: Vec<Option<Vec<u8>>>) -> bool {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            !xs.iter().flatten().any(|ys| ys.iter().any(|&y| y != 0))
+        }
+
+        CALLS.store(0, Ordering::SeqCst);
+        for seed in 0..10 {
+            let witness = QuickCheck::new()
+                .gen(Gen::from_seed(100, seed))
+                .run_property(prop as fn(Vec<Option<Vec<u8>>>) -> bool)
+                .unwrap();
+            assert_eq!(witness, vec!["[Some([1])]".to_string()]);
+        }
+        // The passes try candidates in the same order as a single unlimited
+        // pass, and never retry one that passed, so they make exactly as
+        // many calls.
+        assert_eq!(CALLS.load(Ordering::SeqCst), 762);
+    }
+
+    #[test]
+    fn runner_reports_unshrunk_failures() {
+        fn prop(xs: Vec<u8>) -> bool {
+            xs.len() < 3
+        }
+        let mut runner = QuickCheck::new()
+            .gen(Gen::from_seed(100, 0))
+            .runner(prop as fn(Vec<u8>) -> bool);
+        let unshrunk = loop {
+            assert!(!runner.is_finished(), "property never failed");
+            match runner.step() {
+                CaseOutcome::Pass => {}
+                CaseOutcome::Discard => panic!("property never discards"),
+                CaseOutcome::Failed(r) => break r,
+            }
+        };
+        assert_eq!(runner.discarded(), 0);
+        assert_eq!(unshrunk.shrink_steps(), 0);
+
+        let shrunk = runner.shrink_last_failure();
+        assert!(shrunk.is_failure());
+        assert_eq!(shrunk.arguments, vec![format!("{:?}", [0u8, 0, 0])]);
+        assert_ne!(shrunk.arguments, unshrunk.arguments);
+    }
+
+    #[test]
+    fn runner_shrinks_only_when_asked() {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        fn prop(xs: Vec<u8>) -> bool {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            xs.len() < 3
+        }
+        let mut runner = QuickCheck::new()
+            .gen(Gen::from_seed(100, 0))
+            .runner(prop as fn(Vec<u8>) -> bool);
+        while let CaseOutcome::Pass = runner.step() {}
+        let stepped = CALLS.load(Ordering::SeqCst);
+        assert_eq!(stepped as u64, runner.passed() + 1);
+
+        let shrunk = runner.shrink_last_failure();
+        let shrinking = CALLS.load(Ordering::SeqCst);
+        assert!(shrinking > stepped + 1);
+        assert_eq!(runner.shrink_last_failure().arguments, shrunk.arguments);
+        assert_eq!(CALLS.load(Ordering::SeqCst), shrinking);
+    }
+
+    #[test]
+    fn config_default_matches_env() {
+        let config = Config::default();
+        assert_eq!(config.tests, super::qc_tests());
+        assert_eq!(config.min_tests_passed, super::qc_min_tests_passed());
+        assert_eq!(config.gen_size, super::qc_gen_size());
+        assert_eq!(config.seed, super::qc_seed());
+        assert_eq!(config.start_case, super::qc_start_case());
+
+        let qc = QuickCheck::new();
+        assert_eq!(qc.tests, config.tests);
+        assert_eq!(qc.max_tests, config.max_tests);
+        assert_eq!(qc.min_tests_passed, config.min_tests_passed);
+        assert_eq!(qc.gen.size(), config.gen_size);
+    }
+
+    #[test]
+    fn config_matches_builder() {
+        let from_config = QuickCheck::from(Config {
+            tests: 7,
+            max_tests: 70,
+            min_tests_passed: 3,
+            gen_size: 11,
+            seed: Some(1234),
+            ..Config::default()
+        });
+        let from_builder = QuickCheck::new()
+            .gen(Gen::from_seed(11, 1234))
+            .tests(7)
+            .max_tests(70)
+            .min_tests_passed(3);
+        assert_eq!(from_config.tests, from_builder.tests);
+        assert_eq!(from_config.max_tests, from_builder.max_tests);
+        assert_eq!(
+            from_config.min_tests_passed,
+            from_builder.min_tests_passed
+        );
+        assert_eq!(from_config.gen.size(), from_builder.gen.size());
+        assert_eq!(from_config.gen.seed(), from_builder.gen.seed());
+
+        fn prop(xs: Vec<u8>) -> bool {
+            xs.len() < 5
+        }
+        let config = Config { seed: Some(1234), ..Config::default() };
+        let a = QuickCheck::from(config)
+            .quicktest(prop as fn(Vec<u8>) -> bool)
+            .unwrap_err();
+        let b = QuickCheck::new()
+            .gen(Gen::from_seed(super::qc_gen_size(), 1234))
+            .quicktest(prop as fn(Vec<u8>) -> bool)
+            .unwrap_err();
+        assert_eq!(a.arguments, b.arguments);
+    }
+
+    #[test]
+    fn used_generators_are_reseeded() {
+        fn prop(xs: Vec<u8>) -> bool {
+            xs.len() < 5
+        }
+        let mut g = Gen::from_seed(100, 1234);
+        let _: u64 = g.gen();
+        let mut qc = QuickCheck::new().gen(g);
+        assert_ne!(qc.seed(), 1234);
+        let a = qc.quicktest(prop as fn(Vec<u8>) -> bool).unwrap_err();
+        let b = QuickCheck::new()
+            .gen(Gen::from_seed(100, qc.seed()))
+            .quicktest(prop as fn(Vec<u8>) -> bool)
+            .unwrap_err();
+        assert_eq!(a.arguments, b.arguments);
+        assert_eq!(a.reproduce, b.reproduce);
+    }
+
+    #[test]
+    fn seed_survives_run() {
+        fn prop(xs: Vec<u8>) -> bool {
+            xs.len() < 5
+        }
+        let mut qc = QuickCheck::new().gen(Gen::from_seed(100, 1234));
+        assert_eq!(qc.seed(), 1234);
+        qc.quicktest(prop as fn(Vec<u8>) -> bool).unwrap_err();
+        assert_eq!(qc.seed(), 1234);
+
+        let mut qc = QuickCheck::new().gen(Gen::new(100));
+        let a = qc.quicktest(prop as fn(Vec<u8>) -> bool).unwrap_err();
+        let b = QuickCheck::new()
+            .gen(Gen::from_seed(100, qc.seed()))
+            .quicktest(prop as fn(Vec<u8>) -> bool)
+            .unwrap_err();
+        assert_eq!(a.arguments, b.arguments);
+    }
+
+    #[test]
+    fn run_property() {
+        fn prop(n: u32, s: String) -> bool {
+            n < 1_000 || s.is_empty()
+        }
+        let args = QuickCheck::new()
+            .run_property(prop as fn(u32, String) -> bool)
+            .unwrap();
+        assert_eq!(args.len(), 2);
+        assert_eq!(args[0], "1000");
+
+        fn pass(_: u32) -> bool {
+            true
+        }
+        assert_eq!(
+            QuickCheck::new().run_property(pass as fn(u32) -> bool),
+            None
+        );
+    }
+
+    #[test]
+    fn arguments_are_cloned_once_per_call() {
+        use std::fmt;
+
+        use crate::Arbitrary;
+
+        static CLONES: AtomicUsize = AtomicUsize::new(0);
+        static FORMATS: AtomicUsize = AtomicUsize::new(0);
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        /// A 1MiB argument that counts how often it is cloned and
+        /// formatted.
+        struct Big(Vec<u8>);
+
+        impl Clone for Big {
+            fn clone(&self) -> Big {
+                CLONES.fetch_add(1, Ordering::SeqCst);
+                Big(self.0.clone())
+            }
+        }
+
+        impl fmt::Debug for Big {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                FORMATS.fetch_add(1, Ordering::SeqCst);
+                write!(f, "Big({})", self.0.len())
+            }
+        }
+
+        impl Arbitrary for Big {
+            fn arbitrary(_: &mut Gen) -> Big {
+                Big(vec![0; 1 << 20])
+            }
+
+            fn shrink(&self) -> Box<dyn Iterator<Item = Big>> {
+                let half = self.0[..self.0.len() / 2].to_vec();
+                Box::new(Some(Big(half)).into_iter())
+            }
+        }
+
+        fn reset() {
+            CLONES.store(0, Ordering::SeqCst);
+            FORMATS.store(0, Ordering::SeqCst);
+            CALLS.store(0, Ordering::SeqCst);
+        }
+
+        fn pass(_: Big) -> bool {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            true
+        }
+        reset();
+        QuickCheck::new().tests(20).quickcheck(pass as fn(Big) -> bool);
+        assert_eq!(CALLS.load(Ordering::SeqCst), 20);
+        assert_eq!(CLONES.load(Ordering::SeqCst), 20);
+        assert_eq!(FORMATS.load(Ordering::SeqCst), 0);
+
+        fn fail(b: Big) -> bool {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            b.0.len() < 1024
+        }
+        reset();
+        let r =
+            QuickCheck::new().quicktest(fail as fn(Big) -> bool).unwrap_err();
+        assert_eq!(r.arguments, vec!["Big(1024)".to_string()]);
+        // The failing call, the call that runs it again to shrink it, then
+        // 10 failing halvings down to 1024 and one passing one. Only the
+        // unshrunk witness, once for each of its two calls, and the shrunk
+        // witness are formatted.
+        assert_eq!(CALLS.load(Ordering::SeqCst), 13);
+        assert_eq!(CLONES.load(Ordering::SeqCst), 13);
+        assert_eq!(FORMATS.load(Ordering::SeqCst), 3);
+    }
+
+    /// The size of the generator, as observed when generating it.
+    #[derive(Clone, Debug)]
+    struct ObservedSize(usize);
+
+    impl crate::Arbitrary for ObservedSize {
+        fn arbitrary(g: &mut Gen) -> ObservedSize {
+            ObservedSize(g.size())
+        }
+    }
+
+    #[test]
+    fn size_range_grows_size() {
+        use std::sync::Mutex;
+
+        static SIZES: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+
+        fn record(s: ObservedSize) -> bool {
+            SIZES.lock().unwrap().push(s.0);
+            true
+        }
+
+        let sizes = |qc: QuickCheck| {
+            SIZES.lock().unwrap().clear();
+            qc.tests(50).quickcheck(record as fn(ObservedSize) -> bool);
+            SIZES.lock().unwrap().clone()
+        };
+
+        let grown = sizes(QuickCheck::new().size_range(5, 200));
+        assert_eq!(grown.len(), 50);
+        assert_eq!((grown[0], grown[49]), (5, 200));
+        assert!(grown.windows(2).all(|w| w[0] <= w[1]), "{:?}", grown);
+
+        let constant = sizes(
+            QuickCheck::new().size_range(5, 200).with_generator_size(37),
+        );
+        assert_eq!(constant, vec![37; 50]);
+    }
+
+    #[test]
+    fn gen_size_schedule_sets_size() {
+        use std::sync::Mutex;
+
+        static SIZES: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+
+        fn record(s: ObservedSize) -> bool {
+            SIZES.lock().unwrap().push(s.0);
+            true
+        }
+        fn schedule(case: u64, tests: u64) -> usize {
+            (case * 1000 + tests) as usize
+        }
+
+        QuickCheck::new()
+            .size_range(5, 200)
+            .gen_size_schedule(schedule)
+            .tests(3)
+            .quickcheck(record as fn(ObservedSize) -> bool);
+        assert_eq!(*SIZES.lock().unwrap(), vec![3, 1003, 2003]);
+
+        fn prop(s: ObservedSize) -> bool {
+            s.0 < 1000
+        }
+        let r = QuickCheck::new()
+            .gen_size_schedule(schedule)
+            .quicktest(prop as fn(ObservedSize) -> bool)
+            .unwrap_err();
+        assert!(r.failed_msg().contains("\nGenerator size: 1100"));
+    }
+
+    #[test]
+    fn size_range_reports_size() {
+        fn prop(s: ObservedSize) -> bool {
+            s.0 < 50
+        }
+        let r = QuickCheck::new()
+            .size_range(0, 99)
+            .quicktest(prop as fn(ObservedSize) -> bool)
+            .unwrap_err();
+        assert!(r.failed_msg().contains("\nGenerator size: 50"));
+
+        let r = QuickCheck::new()
+            .with_generator_size(50)
+            .quicktest(prop as fn(ObservedSize) -> bool)
+            .unwrap_err();
+        assert!(!r.failed_msg().contains("Generator size"));
+    }
+
+    #[test]
+    fn shrinking_uses_failure_context() {
+        use std::sync::atomic::AtomicBool;
+
+        use crate::{Arbitrary, FailureContext};
+
+        static SHORTCUT: AtomicBool = AtomicBool::new(false);
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        /// A value whose shrinker jumps to the limit named in the failure
+        /// message, if `SHORTCUT` is set.
+        #[derive(Clone, Debug)]
+        struct Level(u64);
+
+        impl Arbitrary for Level {
+            fn arbitrary(_: &mut Gen) -> Level {
+                Level(1_000_000)
+            }
+
+            fn shrink(&self) -> Box<dyn Iterator<Item = Level>> {
+                Box::new(self.0.shrink().map(Level))
+            }
+
+            fn shrink_with_context(
+                &self,
+                ctx: &FailureContext,
+            ) -> Box<dyn Iterator<Item = Level>> {
+                let limit = ctx
+                    .message()
+                    .filter(|_| SHORTCUT.load(Ordering::SeqCst))
+                    .and_then(|msg| msg.split("limit ").nth(1))
+                    .map(|s| s.chars().take_while(char::is_ascii_digit))
+                    .and_then(|digits| digits.collect::<String>().parse().ok())
+                    .filter(|&limit| limit < self.0);
+                Box::new(limit.map(Level).into_iter().chain(self.shrink()))
+            }
+        }
+
+        fn prop(level: Level) {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            assert!(level.0 < 1_000, "over the limit 1000");
+        }
+
+        let witness = |shortcut| {
+            SHORTCUT.store(shortcut, Ordering::SeqCst);
+            CALLS.store(0, Ordering::SeqCst);
+            let args = QuickCheck::new().run_property(prop as fn(Level));
+            assert_eq!(args, Some(vec!["Level(1000)".to_string()]));
+            CALLS.load(Ordering::SeqCst)
+        };
+        let plain = witness(false);
+        let shortcut = witness(true);
+        assert!(shortcut < plain, "{} >= {}", shortcut, plain);
+    }
+
+    #[test]
+    fn interrupted_shrinking() {
+        static FAILURES: AtomicUsize = AtomicUsize::new(0);
+
+        fn prop(n: u32) -> bool {
+            if n >= 1_000 && FAILURES.fetch_add(1, Ordering::SeqCst) == 3 {
+                request_stop();
+            }
+            n < 1_000
+        }
+        let r = QuickCheck::new()
+            .print_witness_on_interrupt(true)
+            .quicktest(prop as fn(u32) -> bool)
+            .unwrap_err();
+        assert!(r.interrupted);
+        assert!(r.arguments[0].parse::<u32>().unwrap() >= 1_000);
+        assert!(r.failed_msg().contains("(Shrinking was interrupted.)"));
+
+        // A stop requested before a run doesn't interrupt it. This is part
+        // of the same test, since requests interrupt every run in progress.
+        fn big(n: u32) -> bool {
+            n < 1_000
+        }
+        request_stop();
+        let r = QuickCheck::new()
+            .print_witness_on_interrupt(true)
+            .quicktest(big as fn(u32) -> bool)
+            .unwrap_err();
+        assert!(!r.interrupted);
+        assert_eq!(r.arguments, vec!["1000".to_string()]);
+    }
+
+    #[test]
+    fn with_generator_size() {
+        let qc = QuickCheck::new().with_generator_size(7);
+        assert_eq!(qc.gen.size(), 7);
+
+        fn prop(xs: Vec<u8>) -> bool {
+            xs.len() < 7
+        }
+        QuickCheck::new()
+            .with_generator_size(7)
+            .quickcheck(prop as fn(Vec<u8>) -> bool);
+    }
+
+    fn panics_in_range(n: u8) -> u8 {
+        if (100..=120).contains(&n) {
+            panic!("bad input");
+        }
+        n
+    }
+
+    #[test]
+    fn no_panic_shrinks_into_panicking_range() {
+        let failing_case = QuickCheck::new()
+            .tests(1_000)
+            .quicktest(crate::no_panic(panics_in_range as fn(u8) -> u8))
+            .unwrap_err();
+        let n: u8 = failing_case.arguments[0].parse().unwrap();
+        assert!((100..=120).contains(&n), "witness {} out of range", n);
+        assert!(failing_case.err.unwrap().contains("bad input"));
+    }
+
+    #[test]
+    fn no_panic_passes() {
+        fn double(n: u8) -> u16 {
+            n as u16 * 2
+        }
+        crate::quickcheck(crate::no_panic(double as fn(u8) -> u16));
+    }
+
+    #[test]
+    fn no_panic_result_discards_errors() {
+        fn parse(s: String) -> Result<u8, std::num::ParseIntError> {
+            s.parse()
+        }
+        let passed = QuickCheck::new()
+            .quicktest(crate::no_panic_result(
+                parse as fn(String) -> Result<u8, std::num::ParseIntError>,
+            ))
+            .unwrap();
+        // Nearly every random string fails to parse, and those tests are
+        // discarded rather than counted as passes.
+        assert!(passed < 100);
     }
 
     #[test]
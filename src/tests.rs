@@ -1,11 +1,17 @@
 use std::cmp::Ord;
 use std::collections::hash_map::DefaultHasher;
 use std::collections::{HashMap, HashSet};
+use std::env;
 use std::ffi::CString;
+use std::fs;
 use std::hash::BuildHasherDefault;
 use std::path::PathBuf;
+use std::process;
 
-use super::{quickcheck, Gen, QuickCheck, TestResult};
+use super::{
+    any, assuming, quickcheck, Arbitrary, ArcArbitrary, Gen, QuickCheck,
+    Registered, TestResult,
+};
 
 #[test]
 fn prop_oob() {
@@ -23,6 +29,57 @@ fn prop_oob() {
     }
 }
 
+#[test]
+fn find_all_failures() {
+    fn prop(n: u8) -> bool {
+        n < 10
+    }
+    let failures = QuickCheck::new()
+        .tests(1000)
+        .find_all_failures(prop as fn(u8) -> bool, 3);
+    assert_eq!(failures.len(), 3);
+    assert!(failures.iter().all(|r| r.is_failure()));
+}
+
+#[test]
+fn find_all_failures_none() {
+    fn prop(_: u8) -> bool {
+        true
+    }
+    let failures =
+        QuickCheck::new().find_all_failures(prop as fn(u8) -> bool, 3);
+    assert!(failures.is_empty());
+}
+
+#[test]
+fn persisted_failure_is_replayed_first() {
+    fn prop(n: u32) -> bool {
+        n < 1_000
+    }
+    let path = env::temp_dir()
+        .join(format!("quickcheck-persisted-failure-{}", process::id()));
+    let _ = fs::remove_file(&path);
+
+    let first = QuickCheck::new()
+        .persistence_file(&path)
+        .quicktest(prop as fn(u32) -> bool)
+        .unwrap_err();
+    let persisted = fs::read_to_string(&path).unwrap();
+    assert_eq!(persisted.lines().filter(|l| !l.starts_with('#')).count(), 1);
+    assert!(persisted.contains("(1000)"));
+
+    // With no new tests to run, a failure can only come from the replayed
+    // seed.
+    let replayed = QuickCheck::new()
+        .tests(0)
+        .persistence_file(&path)
+        .quicktest(prop as fn(u32) -> bool)
+        .unwrap_err();
+    assert!(first.is_failure() && replayed.is_failure());
+    assert_eq!(replayed.arguments(), first.arguments());
+    let _ = fs::remove_file(&path);
+}
+
 #[test]
 fn prop_reverse_reverse() {
     fn prop(xs: Vec<usize>) -> bool {
@@ -45,6 +102,17 @@ quickcheck! {
         assert!(false);
         false
     }
+
+    fn prop_macro_where(xs: Vec<u8>) -> bool
+    where
+        Vec<u8>: Ord,
+        u8: Into<u64>,
+    {
+        let mut sorted = xs.clone();
+        sorted.sort();
+        sorted.iter().map(|&x| Into::<u64>::into(x)).sum::<u64>()
+            == xs.iter().map(|&x| x as u64).sum::<u64>()
+    }
 }
 
 #[test]
@@ -254,6 +322,98 @@ fn all_tests_discarded_min_tests_passed_missing() {
     QuickCheck::new().quickcheck(prop_discarded as fn(u8) -> TestResult)
 }
 
+fn is_even(n: &u32) -> bool {
+    n % 2 == 0
+}
+
+#[test]
+fn assuming_discards_and_shrinks() {
+    fn prop_small(n: u32) -> bool {
+        assert!(is_even(&n));
+        n < 10
+    }
+
+    let mut qc = QuickCheck::new();
+    let witness = qc
+        .run_property(assuming(
+            is_even as fn(&u32) -> bool,
+            prop_small as fn(u32) -> bool,
+        ))
+        .unwrap();
+    let n: u32 = witness[0].parse().unwrap();
+    assert!(n >= 10 && is_even(&n), "{}", n);
+
+    fn prop_halving(n: u32) -> bool {
+        (n / 2) * 2 == n
+    }
+    qc.quickcheck(assuming(
+        is_even as fn(&u32) -> bool,
+        prop_halving as fn(u32) -> bool,
+    ));
+    let reasons = qc.discard_reasons();
+    assert_eq!(reasons[0].0.as_deref(), Some("assumption does not hold"));
+}
+
+fn prop_discard_reasons(n: u8) -> TestResult {
+    if n < 192 {
+        TestResult::discard_because("small")
+    } else if n % 2 == 0 {
+        TestResult::discard_because("even")
+    } else {
+        TestResult::passed()
+    }
+}
+
+#[test]
+fn discard_reasons_are_counted() {
+    let mut qc = QuickCheck::new().tests(100).max_tests(100_000);
+    qc.quicktest(prop_discard_reasons as fn(u8) -> TestResult).unwrap();
+    let reasons = qc.discard_reasons();
+    assert_eq!(reasons.len(), 2, "{:?}", reasons);
+    assert_eq!(reasons[0].0.as_deref(), Some("small"));
+    assert_eq!(reasons[1].0.as_deref(), Some("even"));
+    assert!(reasons[0].1 > 2 * reasons[1].1, "{:?}", reasons);
+}
+
+#[test]
+#[should_panic(expected = "not discarded.)\nDiscard reasons: small (")]
+fn discard_reasons_when_exhausted() {
+    QuickCheck::new()
+        .tests(100)
+        .max_tests(200)
+        .min_tests_passed(100)
+        .quickcheck(prop_discard_reasons as fn(u8) -> TestResult)
+}
+
+#[test]
+#[should_panic(
+    expected = "(min_tests_passed is 1000, but at most 100 tests can pass \
+                with tests = 100 and max_tests = 10000.)"
+)]
+fn min_tests_passed_unreachable() {
+    fn prop(_: u8) -> bool {
+        true
+    }
+
+    QuickCheck::new()
+        .tests(100)
+        .max_tests(10_000)
+        .min_tests_passed(1000)
+        .quickcheck(prop as fn(u8) -> bool)
+}
+
+#[test]
+#[should_panic(expected = "Discarded arguments:\n(")]
+fn all_tests_discarded_report_discards() {
+    fn prop_discarded(_: u8) -> TestResult {
+        TestResult::discard()
+    }
+
+    QuickCheck::new()
+        .report_discards(true)
+        .quickcheck(prop_discarded as fn(u8) -> TestResult)
+}
+
 quickcheck! {
     /// The following is a very simplistic test, which only verifies
     /// that our PathBuf::arbitrary does not panic.  Still, that's
@@ -286,3 +446,356 @@ quickcheck! {
         true
     }
 }
+
+/// Stands in for a type from another crate, which can't implement
+/// `Arbitrary` in this one.
+#[derive(Clone, Debug)]
+struct ForeignPoint {
+    x: i32,
+    y: i32,
+}
+
+fn origin_quadrant(g: &mut Gen) -> ForeignPoint {
+    ForeignPoint { x: u16::arbitrary(g) as i32, y: u16::arbitrary(g) as i32 }
+}
+
+#[test]
+fn registered_generator() {
+    fn prop(p: Registered<ForeignPoint>) -> bool {
+        p.0.x >= 0 && p.0.y >= 0
+    }
+    let mut gen = Gen::new(100);
+    gen.install(origin_quadrant);
+    QuickCheck::new()
+        .gen(gen)
+        .quickcheck(prop as fn(Registered<ForeignPoint>) -> bool);
+}
+
+#[test]
+#[should_panic(expected = "no generator installed for type")]
+fn registered_generator_missing() {
+    fn prop(_: Registered<ForeignPoint>) -> bool {
+        true
+    }
+    quickcheck(prop as fn(Registered<ForeignPoint>) -> bool);
+}
+
+mod compat_safe_email {
+    use crate::compat::Gen;
+    use crate::quickcheck;
+
+    /// An old-style impl, as written against quickcheck 0.9.
+    #[derive(Clone, Debug)]
+    struct SafeEmail(String);
+
+    fn domain<G: Gen>(g: &mut G) -> &'static str {
+        let domains = ["example.com", "example.org", "example.net"];
+        domains[g.next_u32() as usize % domains.len()]
+    }
+
+    crate::impl_arbitrary_compat! {
+        impl Arbitrary for SafeEmail {
+            fn arbitrary<G: Gen>(g: &mut G) -> SafeEmail {
+                let len = g.size() % 16 + 1;
+                let user: String = (0..len)
+                    .map(|_| (b'a' + u8::arbitrary(g) % 26) as char)
+                    .collect();
+                let tag = Option::<u8>::arbitrary(g)
+                    .map(|n| format!("+{}", n))
+                    .unwrap_or_default();
+                SafeEmail(format!("{}{}@{}", user, tag, domain(g)))
+            }
+
+            fn shrink(&self) -> Box<dyn Iterator<Item = SafeEmail>> {
+                Box::new(std::iter::empty())
+            }
+        }
+    }
+
+    #[test]
+    fn compat_impl() {
+        fn prop(email: SafeEmail) -> bool {
+            let (user, domain) = email.0.split_at(email.0.find('@').unwrap());
+            !user.is_empty() && domain.starts_with("@example.")
+        }
+        quickcheck(prop as fn(SafeEmail) -> bool);
+    }
+}
+
+#[test]
+fn testable_gen_closure() {
+    QuickCheck::new().quickcheck(|g: &mut Gen| {
+        let xs: Vec<u8> = any(g);
+        let i: usize = any(g);
+        TestResult::from_bool(xs.is_empty() || xs.contains(&xs[i % xs.len()]))
+    });
+}
+
+#[test]
+fn testable_gen_closure_failure() {
+    let r = QuickCheck::new()
+        .quicktest(|g: &mut Gen| {
+            let x = u8::arbitrary(g);
+            if x >= 200 {
+                panic!("too big: {}", x);
+            }
+            TestResult::passed()
+        })
+        .unwrap_err();
+    assert!(r.is_failure(), "{:?}", r);
+    assert!(format!("{:?}", r).contains("err: Some(\"too big: "), "{:?}", r);
+}
+
+/// A value that can't be cloned, since it owns a trait object.
+struct Counter {
+    step: Box<dyn Fn(u32) -> u32>,
+    start: u32,
+}
+
+impl std::fmt::Debug for Counter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Counter {{ start: {} }}", self.start)
+    }
+}
+
+fn counter(g: &mut Gen) -> Counter {
+    let by = u16::arbitrary(g) as u32;
+    Counter {
+        step: Box::new(move |n| n + by),
+        start: u16::arbitrary(g) as u32,
+    }
+}
+
+#[test]
+fn arc_arbitrary_non_clone() {
+    fn prop(c: ArcArbitrary<Counter>) -> bool {
+        (c.step)(c.start) >= c.start
+    }
+    let mut gen = Gen::new(100);
+    gen.install(counter);
+    QuickCheck::new()
+        .gen(gen)
+        .quickcheck(prop as fn(ArcArbitrary<Counter>) -> bool);
+}
+
+#[test]
+fn arc_arbitrary_debug() {
+    fn prop(c: ArcArbitrary<Counter>) -> bool {
+        c.start > 70_000
+    }
+    let mut gen = Gen::new(100);
+    gen.install(counter);
+    let r = QuickCheck::new()
+        .gen(gen)
+        .quicktest(prop as fn(ArcArbitrary<Counter>) -> bool)
+        .unwrap_err();
+    assert!(format!("{:?}", r).contains("arguments: [\"Counter { start: "));
+}
+
+#[test]
+fn check_all_names_failing_property() {
+    let err = std::panic::catch_unwind(|| {
+        QuickCheck::new().check_all(vec![
+            ("reversible", |xs: &Vec<u32>| {
+                let rev: Vec<u32> = xs.iter().rev().cloned().collect();
+                TestResult::from_bool(rev.into_iter().rev().eq(xs.clone()))
+            }),
+            ("short", |xs| TestResult::from_bool(xs.len() < 3)),
+        ])
+    })
+    .unwrap_err();
+    let msg = err.downcast_ref::<String>().unwrap();
+    assert!(msg.contains("Arguments: ([0, 0, 0])"), "{}", msg);
+    assert!(msg.contains("\nFailing properties: short\n"), "{}", msg);
+}
+
+/// A value whose `Debug` output has to be escaped in JSON.
+#[derive(Clone)]
+struct Quoted(u8);
+
+impl std::fmt::Debug for Quoted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Quoted(\"{}\"\n\t\\)", self.0)
+    }
+}
+
+impl Arbitrary for Quoted {
+    fn arbitrary(g: &mut Gen) -> Quoted {
+        Quoted(u8::arbitrary(g))
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Quoted>> {
+        Box::new(self.0.shrink().map(Quoted))
+    }
+}
+
+#[test]
+fn json_report() {
+    fn prop(q: Quoted, s: String) -> bool {
+        if q.0 >= 10 && s.contains('"') {
+            panic!("bad \"quote\"\nsecond line");
+        }
+        true
+    }
+    let path = env::temp_dir()
+        .join(format!("quickcheck-json-report-{}.json", process::id()));
+    let _ = fs::remove_file(&path);
+    for _ in 0..2 {
+        let path = path.clone();
+        std::panic::catch_unwind(move || {
+            QuickCheck::new()
+                .json_report(path)
+                .quickcheck(prop as fn(Quoted, String) -> bool)
+        })
+        .unwrap_err();
+    }
+
+    // Each failure is appended as a line of its own.
+    let contents = fs::read_to_string(&path).unwrap();
+    let _ = fs::remove_file(&path);
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 2, "{}", contents);
+    let report = Json::parse(lines[1]);
+    assert!(matches!(report.get("property"), Json::Str(property)
+        if property.contains("Quoted") && property.ends_with("-> bool")));
+    assert_eq!(
+        report.get("arguments"),
+        &Json::Array(vec![
+            Json::Str("Quoted(\"10\"\n\t\\)".to_string()),
+            Json::Str("\"\\\"\"".to_string()),
+        ])
+    );
+    // Like in the panic message, the error is the panic message formatted
+    // with `Debug`.
+    assert_eq!(
+        report.get("error"),
+        &Json::Str(format!("{:?}", "bad \"quote\"\nsecond line"))
+    );
+    assert!(matches!(report.get("shrink_steps"), Json::Number(n) if n != "0"));
+    assert!(matches!(report.get("seed"), Json::Str(seed)
+        if seed.parse::<u64>().is_ok()));
+    assert!(matches!(report.get("case"), Json::Number(_)));
+}
+
+/// Just enough of a JSON parser to check the reports of `json_report`.
+#[derive(Debug, PartialEq)]
+enum Json {
+    Null,
+    Number(String),
+    Str(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+type JsonChars<'a> = std::iter::Peekable<std::str::Chars<'a>>;
+
+impl Json {
+    fn parse(s: &str) -> Json {
+        let mut chars = s.chars().peekable();
+        let json = Json::parse_value(&mut chars);
+        Json::skip_whitespace(&mut chars);
+        assert_eq!(chars.next(), None, "trailing characters");
+        json
+    }
+
+    fn get(&self, key: &str) -> &Json {
+        match *self {
+            Json::Object(ref fields) => {
+                &fields.iter().find(|&(k, _)| k == key).unwrap().1
+            }
+            _ => panic!("{:?} is not an object", self),
+        }
+    }
+
+    fn skip_whitespace(chars: &mut JsonChars<'_>) {
+        while let Some(' ' | '\n' | '\r' | '\t') = chars.peek() {
+            chars.next();
+        }
+    }
+
+    fn parse_value(chars: &mut JsonChars<'_>) -> Json {
+        Json::skip_whitespace(chars);
+        match *chars.peek().expect("unexpected end of input") {
+            'n' => {
+                assert!("null".chars().all(|c| chars.next() == Some(c)));
+                Json::Null
+            }
+            '"' => Json::Str(Json::parse_string(chars)),
+            '[' => {
+                let mut values = vec![];
+                Json::parse_sequence(chars, ']', |chars| {
+                    values.push(Json::parse_value(chars))
+                });
+                Json::Array(values)
+            }
+            '{' => {
+                let mut fields = vec![];
+                Json::parse_sequence(chars, '}', |chars| {
+                    Json::skip_whitespace(chars);
+                    let key = Json::parse_string(chars);
+                    Json::skip_whitespace(chars);
+                    assert_eq!(chars.next(), Some(':'));
+                    fields.push((key, Json::parse_value(chars)));
+                });
+                Json::Object(fields)
+            }
+            _ => {
+                let mut n = String::new();
+                while let Some(&c @ ('0'..='9' | '-')) = chars.peek() {
+                    n.push(c);
+                    chars.next();
+                }
+                assert!(!n.is_empty(), "unexpected {:?}", chars.peek());
+                Json::Number(n)
+            }
+        }
+    }
+
+    /// Parses comma separated elements with `parse_element` up to `end`.
+    fn parse_sequence<F>(chars: &mut JsonChars<'_>, end: char, mut element: F)
+    where
+        F: FnMut(&mut JsonChars<'_>),
+    {
+        chars.next();
+        Json::skip_whitespace(chars);
+        if chars.peek() == Some(&end) {
+            chars.next();
+            return;
+        }
+        loop {
+            element(chars);
+            Json::skip_whitespace(chars);
+            match chars.next() {
+                Some(',') => {}
+                Some(c) if c == end => return,
+                c => panic!("unexpected {:?}", c),
+            }
+        }
+    }
+
+    fn parse_string(chars: &mut JsonChars<'_>) -> String {
+        assert_eq!(chars.next(), Some('"'));
+        let mut s = String::new();
+        loop {
+            match chars.next().expect("unterminated string") {
+                '"' => return s,
+                '\\' => s.push(match chars.next().unwrap() {
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    'u' => {
+                        let hex: String = chars.by_ref().take(4).collect();
+                        let n = u32::from_str_radix(&hex, 16).unwrap();
+                        std::char::from_u32(n).unwrap()
+                    }
+                    c @ ('"' | '\\' | '/') => c,
+                    c => panic!("invalid escape {:?}", c),
+                }),
+                c => {
+                    assert!(c >= ' ', "unescaped control character");
+                    s.push(c)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,752 @@
+/*!
+Structured text for testing parsers and tokenizers.
+
+The `Arbitrary` impl for `String` produces fairly uniform Unicode, which is
+rarely accepted by the first stage of a lexer. The types in this module
+instead generate text with a known shape, and shrink in a way that preserves
+that shape. Each type respects `Gen::size()` for the length of the text it
+generates.
+
+# Example
+
+```rust
+use quickcheck::{quickcheck, text::NumberLiteral};
+
+fn prop(n: NumberLiteral) -> bool {
+    let s = n.as_str();
+    if s.starts_with("0x") || s.starts_with("0X") {
+        u64::from_str_radix(&s[2..], 16).is_ok()
+    } else {
+        s.parse::<f64>().is_ok()
+    }
+}
+quickcheck(prop as fn(NumberLiteral) -> bool);
+```
+*/
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::iter::once;
+
+use crate::{Arbitrary, DistributionInfo, Gen};
+
+/// Characters that may appear in an identifier. The first `IDENT_START`
+/// characters may also start one.
+const IDENT_CHARS: &[u8] =
+    b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_0123456789";
+const IDENT_START: usize = 53;
+
+/// An identifier matching `[A-Za-z_][A-Za-z0-9_]*`.
+///
+/// Identifiers are never empty, and shrink toward `a`.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Identifier(String);
+
+impl Identifier {
+    /// Returns the identifier as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Consumes the identifier and returns the underlying string.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Arbitrary for Identifier {
+    fn arbitrary(g: &mut Gen) -> Identifier {
+        let len = g.gen_len() + 1;
+        let mut ident = String::with_capacity(len);
+        ident.push(IDENT_CHARS[g.gen_value_range(0..IDENT_START)] as char);
+        for _ in 1..len {
+            ident.push(
+                IDENT_CHARS[g.gen_value_range(0..IDENT_CHARS.len())] as char,
+            );
+        }
+        Identifier(ident)
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Identifier>> {
+        // Shrink the position of each character in `IDENT_CHARS`, which
+        // moves every character toward `a`.
+        let indices: Vec<usize> = self
+            .0
+            .bytes()
+            .map(|b| IDENT_CHARS.iter().position(|&c| c == b).unwrap())
+            .collect();
+        Box::new(
+            indices
+                .shrink()
+                .filter(|xs| matches!(xs.first(), Some(&i) if i < IDENT_START))
+                .map(|xs| {
+                    Identifier(
+                        xs.into_iter()
+                            .map(|i| IDENT_CHARS[i] as char)
+                            .collect(),
+                    )
+                }),
+        )
+    }
+}
+
+/// Whitespace characters generated by `Whitespace`, in shrinking order.
+const WHITESPACE: &[char] =
+    &[' ', '\t', '\n', '\r', '\u{0B}', '\u{A0}', '\u{2028}'];
+
+/// A possibly empty run of whitespace.
+///
+/// This mixes ASCII spaces, tabs, line feeds, carriage returns (including
+/// `\r\n` pairs) and vertical tabs with the Unicode no-break space and line
+/// separator. Every character satisfies `char::is_whitespace`. Whitespace
+/// shrinks toward fewer characters, each of which is a space.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Whitespace(String);
+
+impl Whitespace {
+    /// Returns the whitespace as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Consumes the whitespace and returns the underlying string.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl fmt::Display for Whitespace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Arbitrary for Whitespace {
+    fn arbitrary(g: &mut Gen) -> Whitespace {
+        let len = g.gen_len();
+        let mut ws = String::with_capacity(len);
+        let mut count = 0;
+        while count < len {
+            // One extra slot is reserved for a `\r\n` pair, if it fits.
+            let pair = usize::from(len - count >= 2);
+            match g.gen_value_range(0..WHITESPACE.len() + pair) {
+                i if i == WHITESPACE.len() => {
+                    ws.push_str("\r\n");
+                    count += 2;
+                }
+                i => {
+                    ws.push(WHITESPACE[i]);
+                    count += 1;
+                }
+            }
+        }
+        Whitespace(ws)
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Whitespace>> {
+        let indices: Vec<usize> = self
+            .0
+            .chars()
+            .map(|c| WHITESPACE.iter().position(|&w| w == c).unwrap())
+            .collect();
+        Box::new(indices.shrink().map(|xs| {
+            Whitespace(xs.into_iter().map(|i| WHITESPACE[i]).collect())
+        }))
+    }
+}
+
+/// Characters that may follow a `\` in a `QuotedString`.
+const ESCAPES: &[char] = &['"', '\\', 'n', 'r', 't', '0'];
+
+/// A single character of a quoted string's contents.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum QuotedPiece {
+    /// A character written as is.
+    Char(char),
+    /// A character written with a leading `\`.
+    Escape(char),
+}
+
+fn is_plain(c: char) -> bool {
+    !c.is_control() && c != '"' && c != '\\'
+}
+
+impl Arbitrary for QuotedPiece {
+    fn arbitrary(g: &mut Gen) -> QuotedPiece {
+        if g.gen_value_range(0..4) == 0 {
+            return QuotedPiece::Escape(*g.choose(ESCAPES).unwrap());
+        }
+        loop {
+            let c = char::arbitrary(g);
+            if is_plain(c) {
+                return QuotedPiece::Char(c);
+            }
+        }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = QuotedPiece>> {
+        match *self {
+            QuotedPiece::Escape(_) => Box::new(once(QuotedPiece::Char('a'))),
+            QuotedPiece::Char(c) => Box::new(
+                c.shrink().filter(|&c| is_plain(c)).map(QuotedPiece::Char),
+            ),
+        }
+    }
+}
+
+/// A double quoted string literal, such as `"a\"b\\n"`.
+///
+/// The contents mix plain characters with the escape sequences `\"`, `\\`,
+/// `\n`, `\r`, `\t` and `\0`. Plain characters are never control characters,
+/// quotes or backslashes, so the literal always ends at its final quote.
+/// Shrinking removes characters and replaces escapes with plain characters.
+#[derive(Clone, Eq, Hash, PartialEq)]
+pub struct QuotedString {
+    pieces: Vec<QuotedPiece>,
+    literal: String,
+}
+
+impl QuotedString {
+    fn new(pieces: Vec<QuotedPiece>) -> QuotedString {
+        let mut literal = String::from("\"");
+        for piece in &pieces {
+            match *piece {
+                QuotedPiece::Char(c) => literal.push(c),
+                QuotedPiece::Escape(c) => {
+                    literal.push('\\');
+                    literal.push(c);
+                }
+            }
+        }
+        literal.push('"');
+        QuotedString { pieces, literal }
+    }
+
+    /// Returns the literal, including its surrounding quotes, as a string
+    /// slice.
+    pub fn as_str(&self) -> &str {
+        &self.literal
+    }
+
+    /// Returns the string that the literal denotes, with its quotes removed
+    /// and its escape sequences replaced by the characters they represent.
+    pub fn value(&self) -> String {
+        self.pieces
+            .iter()
+            .map(|piece| match *piece {
+                QuotedPiece::Char(c) => c,
+                QuotedPiece::Escape('n') => '\n',
+                QuotedPiece::Escape('r') => '\r',
+                QuotedPiece::Escape('t') => '\t',
+                QuotedPiece::Escape('0') => '\0',
+                QuotedPiece::Escape(c) => c,
+            })
+            .collect()
+    }
+}
+
+impl fmt::Debug for QuotedString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("QuotedString").field(&self.literal).finish()
+    }
+}
+
+impl fmt::Display for QuotedString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.literal.fmt(f)
+    }
+}
+
+impl Arbitrary for QuotedString {
+    fn arbitrary(g: &mut Gen) -> QuotedString {
+        QuotedString::new(Arbitrary::arbitrary(g))
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = QuotedString>> {
+        Box::new(self.pieces.shrink().map(QuotedString::new))
+    }
+}
+
+/// Base characters followed by one or more combining marks.
+const COMBINING: &[&str] = &[
+    "e\u{301}",
+    "a\u{300}\u{301}\u{302}\u{303}\u{304}",
+    "Z\u{351}\u{36B}\u{343}\u{36A}\u{302}\u{36B}\u{33E}",
+    "\u{915}\u{94D}\u{937}\u{93F}",
+    "\u{301}",
+];
+
+/// Bidirectional controls, with both matched and unmatched pops.
+const BIDI: &[&str] = &[
+    "\u{202E}abc\u{202C}",
+    "\u{202E}abc",
+    "\u{202C}",
+    "\u{2067}\u{5D0}\u{5D1}\u{2069}",
+    "\u{202B}\u{202A}x\u{202C}",
+    "\u{200F}",
+];
+
+/// Emoji joined into a single grapheme with zero width joiners or
+/// modifiers.
+const ZWJ: &[&str] = &[
+    "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}\u{200D}\u{1F466}",
+    "\u{1F3F3}\u{FE0F}\u{200D}\u{1F308}",
+    "\u{1F469}\u{1F3FD}\u{200D}\u{1F4BB}",
+    "\u{1F44D}\u{1F3FF}",
+];
+
+/// Strings whose NFC and NFD forms differ.
+const NORMALIZATION: &[&str] = &[
+    "\u{E9}",
+    "\u{C5}",
+    "A\u{30A}",
+    "\u{212B}",
+    "s\u{323}\u{307}",
+    "\u{1E0B}\u{323}",
+    "\u{AC00}",
+    "\u{1100}\u{1161}\u{11A8}",
+];
+
+/// Every group of sequences that `TrickyString` splices in.
+const TRICKY: &[&[&str]] = &[COMBINING, BIDI, ZWJ, NORMALIZATION];
+
+/// A part of a `TrickyString`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum TrickyPiece {
+    /// A single arbitrary character.
+    Char(char),
+    /// A sequence from one of the `TRICKY` groups.
+    Sequence(&'static str),
+}
+
+/// A string that mixes arbitrary characters with sequences that tend to
+/// break text handling.
+///
+/// The `Arbitrary` impl for `String` picks each character independently, so
+/// it rarely produces multi-character sequences with special meaning. This
+/// type splices in sequences from a built-in table: characters followed by
+/// several combining marks, bidirectional overrides and isolates (with and
+/// without their closing pop), emoji joined with zero width joiners, and
+/// strings whose NFC and NFD forms differ.
+///
+/// The string has fewer than `Gen::size()` characters. Shrinking first tries
+/// to remove each spliced sequence as a whole, and then shrinks the string
+/// character by character.
+#[derive(Clone, Eq, Hash, PartialEq)]
+pub struct TrickyString {
+    pieces: Vec<TrickyPiece>,
+    string: String,
+}
+
+impl TrickyString {
+    fn new(pieces: Vec<TrickyPiece>) -> TrickyString {
+        let mut string = String::new();
+        for piece in &pieces {
+            match *piece {
+                TrickyPiece::Char(c) => string.push(c),
+                TrickyPiece::Sequence(seq) => string.push_str(seq),
+            }
+        }
+        TrickyString { pieces, string }
+    }
+
+    /// Returns the string as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.string
+    }
+
+    /// Consumes this value and returns the underlying string.
+    pub fn into_string(self) -> String {
+        self.string
+    }
+}
+
+impl fmt::Debug for TrickyString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("TrickyString").field(&self.string).finish()
+    }
+}
+
+impl fmt::Display for TrickyString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.string.fmt(f)
+    }
+}
+
+impl Arbitrary for TrickyString {
+    fn arbitrary(g: &mut Gen) -> TrickyString {
+        let size = g.gen_len();
+        let mut pieces = vec![];
+        let mut len = 0;
+        while len < size {
+            if g.gen_value_range(0..4) == 0 {
+                let group = *g.choose(TRICKY).unwrap();
+                let seq = *g.choose(group).unwrap();
+                let seq_len = seq.chars().count();
+                if len + seq_len <= size {
+                    pieces.push(TrickyPiece::Sequence(seq));
+                    len += seq_len;
+                    continue;
+                }
+            }
+            pieces.push(TrickyPiece::Char(char::arbitrary(g)));
+            len += 1;
+        }
+        TrickyString::new(pieces)
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = TrickyString>> {
+        let pieces = self.pieces.clone();
+        let without_sequences =
+            (0..pieces.len()).filter_map(move |i| match pieces[i] {
+                TrickyPiece::Char(_) => None,
+                TrickyPiece::Sequence(_) => {
+                    let mut pieces = pieces.clone();
+                    pieces.remove(i);
+                    Some(TrickyString::new(pieces))
+                }
+            });
+        let chars: Vec<char> = self.string.chars().collect();
+        let by_char = chars.shrink().map(|chars| {
+            TrickyString::new(
+                chars.into_iter().map(TrickyPiece::Char).collect(),
+            )
+        });
+        Box::new(without_sequences.chain(by_char))
+    }
+}
+
+/// Generates a number with at most `digits` digits in the given radix.
+fn gen_digits(g: &mut Gen, radix: u64, digits: usize) -> u64 {
+    let end = u32::try_from(digits)
+        .ok()
+        .and_then(|digits| radix.checked_pow(digits))
+        .unwrap_or(u64::MAX);
+    g.gen_value_range(0..end)
+}
+
+/// The components of a `NumberLiteral`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum Number {
+    /// `123`
+    Integer(u64),
+    /// `123.45`
+    Decimal(u64, u64),
+    /// `1e10`, `1.5E-3`
+    Exponent(u64, Option<u64>, i16, bool),
+    /// `0x1f`, `0X1F`
+    Hex(u64, bool),
+}
+
+/// A numeric literal in decimal, fractional, exponent or hexadecimal form.
+///
+/// Every literal that does not start with `0x` or `0X` can be parsed with
+/// `str::parse::<f64>`. Hexadecimal literals have at least one digit after
+/// their prefix. Literals are never empty and have at most `Gen::size()`
+/// characters, and only plain integers are generated when there is no room
+/// for the other forms. Shrinking moves toward plain integers, and
+/// ultimately `0`.
+#[derive(Clone, Eq, Hash, PartialEq)]
+pub struct NumberLiteral {
+    number: Number,
+    literal: String,
+}
+
+impl NumberLiteral {
+    fn new(number: Number) -> NumberLiteral {
+        let literal = match number {
+            Number::Integer(n) => n.to_string(),
+            Number::Decimal(int, frac) => format!("{}.{}", int, frac),
+            Number::Exponent(int, frac, exp, upper) => format!(
+                "{}{}{}{}",
+                int,
+                frac.map(|frac| format!(".{}", frac)).unwrap_or_default(),
+                if upper { 'E' } else { 'e' },
+                exp
+            ),
+            Number::Hex(n, false) => format!("0x{:x}", n),
+            Number::Hex(n, true) => format!("0X{:X}", n),
+        };
+        NumberLiteral { number, literal }
+    }
+
+    /// Returns the literal as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.literal
+    }
+}
+
+impl fmt::Debug for NumberLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("NumberLiteral").field(&self.literal).finish()
+    }
+}
+
+impl fmt::Display for NumberLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.literal.fmt(f)
+    }
+}
+
+impl Arbitrary for NumberLiteral {
+    fn arbitrary(g: &mut Gen) -> NumberLiteral {
+        let len = g.gen_len() + 1;
+        // Every form but a plain integer needs room for at least two digits
+        // of each of its parts, plus its separators.
+        let forms = if len < 6 { 1 } else { 4 };
+        let number = match g.gen_value_range(0..forms) {
+            0 => Number::Integer(gen_digits(g, 10, len)),
+            1 => {
+                let digits = (len - 1) / 2;
+                Number::Decimal(
+                    gen_digits(g, 10, digits),
+                    gen_digits(g, 10, digits),
+                )
+            }
+            2 => {
+                // Room for `.`, `e` and a sign.
+                let digits = (len - 3) / 3;
+                let max_exp = (10i16.pow(digits.min(3) as u32) - 1).min(400);
+                let frac = if g.gen() {
+                    Some(gen_digits(g, 10, digits))
+                } else {
+                    None
+                };
+                Number::Exponent(
+                    gen_digits(g, 10, digits),
+                    frac,
+                    g.gen_value_range(-max_exp..=max_exp),
+                    g.gen(),
+                )
+            }
+            _ => Number::Hex(gen_digits(g, 16, len - 2), g.gen()),
+        };
+        NumberLiteral::new(number)
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = NumberLiteral>> {
+        let shrunk: Box<dyn Iterator<Item = Number>> = match self.number {
+            Number::Integer(n) => Box::new(n.shrink().map(Number::Integer)),
+            Number::Decimal(int, frac) => Box::new(
+                once(Number::Integer(int)).chain(
+                    (int, frac)
+                        .shrink()
+                        .map(|(int, frac)| Number::Decimal(int, frac)),
+                ),
+            ),
+            Number::Exponent(int, frac, exp, upper) => {
+                Box::new(once(Number::Integer(int)).chain(
+                    (int, frac, exp, upper).shrink().map(
+                        |(int, frac, exp, upper)| {
+                            Number::Exponent(int, frac, exp, upper)
+                        },
+                    ),
+                ))
+            }
+            Number::Hex(n, upper) => Box::new(once(Number::Integer(n)).chain(
+                (n, upper).shrink().map(|(n, upper)| Number::Hex(n, upper)),
+            )),
+        };
+        Box::new(shrunk.map(NumberLiteral::new))
+    }
+}
+
+/// Implements `DistributionInfo` for text types, which have no special
+/// cases and choose their length with the size.
+macro_rules! distribution_info_text {
+    ($($ty:ty),*) => {$(
+        impl DistributionInfo for $ty {
+            fn respects_size() -> bool {
+                true
+            }
+        }
+    )*};
+}
+
+distribution_info_text!(
+    Identifier,
+    Whitespace,
+    QuotedString,
+    TrickyString,
+    NumberLiteral
+);
+
+#[cfg(test)]
+mod test {
+    use super::{
+        Identifier, NumberLiteral, QuotedString, TrickyPiece, TrickyString,
+        Whitespace, TRICKY,
+    };
+    use crate::{quickcheck, Arbitrary, Gen};
+
+    /// Checks `valid` on a value and every value it shrinks to.
+    fn shrinks_valid<A: Arbitrary>(x: &A, valid: fn(&A) -> bool) -> bool {
+        valid(x) && x.shrink().all(|s| valid(&s))
+    }
+
+    fn valid_identifier(x: &Identifier) -> bool {
+        let mut chars = x.as_str().chars();
+        matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
+
+    fn valid_whitespace(x: &Whitespace) -> bool {
+        x.as_str().chars().all(char::is_whitespace)
+    }
+
+    fn valid_quoted_string(x: &QuotedString) -> bool {
+        let s = x.as_str();
+        if s.len() < 2 || !s.starts_with('"') || !s.ends_with('"') {
+            return false;
+        }
+        // Every quote but the last must be escaped.
+        let mut escaped = false;
+        for c in s[1..s.len() - 1].chars() {
+            match c {
+                '"' if !escaped => return false,
+                '\\' => escaped = !escaped,
+                _ => escaped = false,
+            }
+        }
+        !escaped
+    }
+
+    fn valid_number_literal(x: &NumberLiteral) -> bool {
+        let s = x.as_str();
+        if s.starts_with("0x") || s.starts_with("0X") {
+            u64::from_str_radix(&s[2..], 16).is_ok()
+        } else {
+            s.parse::<f64>().is_ok()
+        }
+    }
+
+    #[test]
+    fn identifiers() {
+        fn prop(x: Identifier) -> bool {
+            x.as_str().len() <= 100 && shrinks_valid(&x, valid_identifier)
+        }
+        quickcheck(prop as fn(Identifier) -> bool);
+    }
+
+    #[test]
+    fn identifiers_shrink_to_a() {
+        let mut x = Identifier("zZ_9q".to_string());
+        while let Some(smaller) = x.shrink().next() {
+            x = smaller;
+        }
+        assert_eq!(x.as_str(), "a");
+    }
+
+    #[test]
+    fn whitespace() {
+        fn prop(x: Whitespace) -> bool {
+            shrinks_valid(&x, valid_whitespace)
+        }
+        quickcheck(prop as fn(Whitespace) -> bool);
+    }
+
+    #[test]
+    fn whitespace_respects_size() {
+        for size in 1..10 {
+            let mut g = Gen::new(size);
+            for _ in 0..100 {
+                let x = Whitespace::arbitrary(&mut g);
+                assert!(x.as_str().chars().count() < size, "{:?}", x);
+            }
+        }
+    }
+
+    #[test]
+    fn quoted_strings() {
+        fn prop(x: QuotedString) -> bool {
+            shrinks_valid(&x, valid_quoted_string)
+        }
+        quickcheck(prop as fn(QuotedString) -> bool);
+    }
+
+    #[test]
+    fn quoted_string_values() {
+        fn prop(x: QuotedString) -> bool {
+            let value = x.value();
+            let escaped: String = value
+                .chars()
+                .flat_map(|c| match c {
+                    '"' => vec!['\\', '"'],
+                    '\\' => vec!['\\', '\\'],
+                    '\n' => vec!['\\', 'n'],
+                    '\r' => vec!['\\', 'r'],
+                    '\t' => vec!['\\', 't'],
+                    '\0' => vec!['\\', '0'],
+                    c => vec![c],
+                })
+                .collect();
+            x.as_str() == format!("\"{}\"", escaped)
+        }
+        quickcheck(prop as fn(QuotedString) -> bool);
+    }
+
+    #[test]
+    fn number_literals() {
+        fn prop(x: NumberLiteral) -> bool {
+            shrinks_valid(&x, valid_number_literal)
+        }
+        quickcheck(prop as fn(NumberLiteral) -> bool);
+    }
+
+    #[test]
+    fn number_literals_respect_size() {
+        for size in 0..30 {
+            let mut g = Gen::new(size);
+            for _ in 0..100 {
+                let x = NumberLiteral::arbitrary(&mut g);
+                assert!(!x.as_str().is_empty());
+                assert!(x.as_str().len() <= size.max(1), "{:?}", x);
+            }
+        }
+    }
+
+    #[test]
+    fn tricky_strings_contain_every_group() {
+        let mut g = Gen::new(100);
+        let strings: Vec<TrickyString> =
+            (0..1_000).map(|_| TrickyString::arbitrary(&mut g)).collect();
+        for group in TRICKY {
+            assert!(strings.iter().any(|s| {
+                group.iter().any(|seq| s.as_str().contains(seq))
+            }));
+        }
+    }
+
+    #[test]
+    fn tricky_strings_respect_size() {
+        fn prop(x: TrickyString) -> bool {
+            x.as_str().chars().count() < 100
+        }
+        quickcheck(prop as fn(TrickyString) -> bool);
+    }
+
+    #[test]
+    fn tricky_strings_shrink_combining_marks() {
+        fn is_combining(c: char) -> bool {
+            ('\u{300}'..='\u{36F}').contains(&c)
+        }
+        let mut x = TrickyString::new(vec![
+            TrickyPiece::Char('x'),
+            TrickyPiece::Sequence("a\u{300}\u{301}\u{302}\u{303}\u{304}"),
+            TrickyPiece::Sequence("\u{202E}abc\u{202C}"),
+            TrickyPiece::Sequence("Z\u{351}\u{36B}\u{343}\u{36A}"),
+        ]);
+        while let Some(smaller) =
+            x.shrink().find(|s| s.as_str().chars().any(is_combining))
+        {
+            x = smaller;
+        }
+        assert_eq!(x.as_str(), "\u{300}");
+    }
+}
@@ -0,0 +1,1003 @@
+/*!
+Wrappers that give types extra trait impls, or change how they are
+generated, for use in generated values.
+
+# Example
+
+Floats aren't `Ord` or `Hash`, so they can't be keys of a `BTreeMap` or
+elements of a `HashSet`. `TotalF64` and `TotalF32` can:
+
+```rust
+use std::collections::BTreeMap;
+
+use quickcheck::{quickcheck, wrappers::TotalF64};
+
+fn prop(m: BTreeMap<TotalF64, u8>) -> bool {
+    m.keys().zip(m.keys().skip(1)).all(|(a, b)| a.total_cmp(b).is_lt())
+}
+quickcheck(prop as fn(BTreeMap<TotalF64, u8>) -> bool);
+```
+*/
+
+use std::any::type_name;
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroU32;
+use std::ops::{Bound, Deref, Range, RangeInclusive};
+
+use crate::{empty_shrinker, Arbitrary, DistributionInfo, Gen};
+
+macro_rules! total_float {
+    ($(#[$doc:meta])* $name:ident, $ty:ty) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, Default)]
+        pub struct $name(pub $ty);
+
+        impl $name {
+            /// Returns the wrapped float.
+            pub fn get(self) -> $ty {
+                self.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = $ty;
+
+            fn deref(&self) -> &$ty {
+                &self.0
+            }
+        }
+
+        impl From<$ty> for $name {
+            fn from(x: $ty) -> $name {
+                $name(x)
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Debug::fmt(&self.0, f)
+            }
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &$name) -> bool {
+                self.0.to_bits() == other.0.to_bits()
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &$name) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $name {
+            fn cmp(&self, other: &$name) -> Ordering {
+                self.0.total_cmp(&other.0)
+            }
+        }
+
+        impl Hash for $name {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.0.to_bits().hash(state)
+            }
+        }
+
+        impl Arbitrary for $name {
+            fn arbitrary(g: &mut Gen) -> $name {
+                $name(<$ty>::arbitrary(g))
+            }
+
+            fn shrink(&self) -> Box<dyn Iterator<Item = $name>> {
+                Box::new(self.0.shrink().map($name))
+            }
+        }
+
+        impl DistributionInfo for $name {
+            fn special_case_probability() -> f64 {
+                <$ty>::special_case_probability()
+            }
+
+            fn respects_size() -> bool {
+                <$ty>::respects_size()
+            }
+        }
+    };
+}
+
+total_float! {
+    /// An `f32` that is totally ordered, and so can be used as a key.
+    ///
+    /// Comparisons follow IEEE 754's `totalOrder` predicate, as implemented
+    /// by `f32::total_cmp`. This means `-0.0` is less than `0.0`, and NaNs
+    /// are ordered by their sign and payload: negative NaNs sort before
+    /// everything else, and positive NaNs after. Two values are equal only
+    /// if they have the same bit pattern, which is also what is hashed.
+    ///
+    /// Values are generated and shrunk like `f32`, including NaN and the
+    /// infinities.
+    TotalF32, f32
+}
+
+total_float! {
+    /// An `f64` that is totally ordered, and so can be used as a key.
+    ///
+    /// Comparisons follow IEEE 754's `totalOrder` predicate, as implemented
+    /// by `f64::total_cmp`. This means `-0.0` is less than `0.0`, and NaNs
+    /// are ordered by their sign and payload: negative NaNs sort before
+    /// everything else, and positive NaNs after. Two values are equal only
+    /// if they have the same bit pattern, which is also what is hashed.
+    ///
+    /// Values are generated and shrunk like `f64`, including NaN and the
+    /// infinities.
+    TotalF64, f64
+}
+
+/// A value of `T` that is only generated once it is used.
+///
+/// Some properties only need an argument that is expensive to generate on
+/// a few of their code paths. A `LazyArbitrary<T>` stores a seed for the
+/// value instead, and generates it when `get` is first called. The value is
+/// then kept, so it's generated at most once. Since it's generated from its
+/// own seed, the value doesn't depend on whether other arguments were
+/// generated before it.
+///
+/// The value is generated with a fresh `Gen` of the same size, so
+/// generators installed with `Gen::install` aren't used for it. Shrinking
+/// generates the value if needed, and shrinks it like `T`. The `Debug` impl
+/// always prints the value, generating a copy of it if it wasn't generated
+/// yet, so witnesses show the value even if the property never used it.
+///
+/// # Example
+///
+/// ```rust
+/// use quickcheck::{quickcheck, wrappers::LazyArbitrary};
+///
+/// fn first_or_fallback(
+///     primary: Vec<u8>,
+///     mut fallback: LazyArbitrary<Vec<u8>>,
+/// ) -> bool {
+///     let first = match primary.first() {
+///         Some(&x) => Some(x),
+///         None => fallback.get().first().copied(),
+///     };
+///     first.is_some() || fallback.get().is_empty()
+/// }
+/// quickcheck(
+///     first_or_fallback as fn(Vec<u8>, LazyArbitrary<Vec<u8>>) -> bool,
+/// );
+/// ```
+pub struct LazyArbitrary<T> {
+    state: Lazy<T>,
+}
+
+/// The state of a `LazyArbitrary`.
+enum Lazy<T> {
+    /// The size and seed of the generator that the value is generated
+    /// with.
+    Unforced {
+        size: usize,
+        seed: u64,
+    },
+    Forced(T),
+}
+
+impl<T: Arbitrary> LazyArbitrary<T> {
+    /// Returns the value, generating it if this is the first call.
+    pub fn get(&mut self) -> &T {
+        if let Lazy::Unforced { size, seed } = self.state {
+            self.state = Lazy::Forced(generate(size, seed));
+        }
+        match self.state {
+            Lazy::Forced(ref value) => value,
+            Lazy::Unforced { .. } => unreachable!(),
+        }
+    }
+
+    /// Returns the value, generating it if it wasn't generated yet.
+    pub fn into_inner(mut self) -> T {
+        self.get();
+        match self.state {
+            Lazy::Forced(value) => value,
+            Lazy::Unforced { .. } => unreachable!(),
+        }
+    }
+
+    /// Returns whether the value has been generated.
+    pub fn is_forced(&self) -> bool {
+        matches!(self.state, Lazy::Forced(_))
+    }
+}
+
+/// Generates the value of a `LazyArbitrary` with the given size and seed.
+fn generate<T: Arbitrary>(size: usize, seed: u64) -> T {
+    T::arbitrary(&mut Gen::from_seed(size, seed))
+}
+
+impl<T: Arbitrary> From<T> for LazyArbitrary<T> {
+    fn from(value: T) -> LazyArbitrary<T> {
+        LazyArbitrary { state: Lazy::Forced(value) }
+    }
+}
+
+impl<T: Clone> Clone for LazyArbitrary<T> {
+    fn clone(&self) -> LazyArbitrary<T> {
+        let state = match self.state {
+            Lazy::Unforced { size, seed } => Lazy::Unforced { size, seed },
+            Lazy::Forced(ref value) => Lazy::Forced(value.clone()),
+        };
+        LazyArbitrary { state }
+    }
+}
+
+impl<T: Arbitrary + fmt::Debug> fmt::Debug for LazyArbitrary<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.state {
+            Lazy::Unforced { size, seed } => {
+                fmt::Debug::fmt(&generate::<T>(size, seed), f)
+            }
+            Lazy::Forced(ref value) => fmt::Debug::fmt(value, f),
+        }
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for LazyArbitrary<T> {
+    fn arbitrary(g: &mut Gen) -> LazyArbitrary<T> {
+        let seed = g.gen();
+        LazyArbitrary { state: Lazy::Unforced { size: g.size(), seed } }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = LazyArbitrary<T>>> {
+        let value = self.clone().into_inner();
+        Box::new(value.shrink().map(LazyArbitrary::from))
+    }
+}
+
+/// The probability is that of `T`. The size is always consulted, since it's
+/// stored for generating the value.
+impl<T: DistributionInfo> DistributionInfo for LazyArbitrary<T> {
+    fn special_case_probability() -> f64 {
+        T::special_case_probability()
+    }
+
+    fn respects_size() -> bool {
+        true
+    }
+}
+
+/// A vector whose shrinker also tries reordering it and removing
+/// duplicates.
+///
+/// Minimal witnesses of failures of properties about collections are often
+/// sorted or free of duplicates, but `Vec<T>`'s shrinker can only reach them
+/// by removing and shrinking elements one by one, if at all. Before those
+/// candidates, a `ShrinkableVec` tries, in order:
+///
+/// * the sorted vector,
+/// * the vector without duplicates, keeping the first of each,
+/// * the reversed vector,
+/// * the first half and the second half of the vector.
+///
+/// A reordered vector is only tried if it's less than the original (as
+/// compared by `Ord`), so shrinking always terminates.
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ShrinkableVec<T>(pub Vec<T>);
+
+impl<T> Deref for ShrinkableVec<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Vec<T> {
+        &self.0
+    }
+}
+
+impl<T> From<Vec<T>> for ShrinkableVec<T> {
+    fn from(xs: Vec<T>) -> ShrinkableVec<T> {
+        ShrinkableVec(xs)
+    }
+}
+
+impl<T: Arbitrary + Ord> Arbitrary for ShrinkableVec<T> {
+    fn arbitrary(g: &mut Gen) -> ShrinkableVec<T> {
+        ShrinkableVec(Vec::arbitrary(g))
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = ShrinkableVec<T>>> {
+        let xs = &self.0;
+        let mut candidates = vec![];
+        let mut sorted = xs.clone();
+        sorted.sort();
+        if sorted < *xs {
+            candidates.push(sorted);
+        }
+        let mut seen = BTreeSet::new();
+        let deduped: Vec<T> =
+            xs.iter().filter(|&x| seen.insert(x)).cloned().collect();
+        if deduped.len() < xs.len() {
+            candidates.push(deduped);
+        }
+        let reversed: Vec<T> = xs.iter().rev().cloned().collect();
+        if reversed < *xs && !candidates.contains(&reversed) {
+            candidates.push(reversed);
+        }
+        if xs.len() >= 2 {
+            let (first, second) = xs.split_at(xs.len() / 2);
+            candidates.push(first.to_vec());
+            candidates.push(second.to_vec());
+        }
+        let shrunk = xs.shrink();
+        Box::new(candidates.into_iter().chain(shrunk).map(ShrinkableVec))
+    }
+}
+
+impl<T: Arbitrary + Ord> DistributionInfo for ShrinkableVec<T> {
+    fn respects_size() -> bool {
+        true
+    }
+}
+
+/// A `&'static str` with arbitrary contents, made by leaking a `String`.
+///
+/// `&'static str` only generates strings from a fixed table. This
+/// generates the same strings as `String` instead, at the cost of leaking
+/// every generated string, including every candidate tried while
+/// shrinking. This is fine for tests of APIs that need `'static` strings,
+/// but the memory is never reclaimed.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct LeakedString(pub &'static str);
+
+impl LeakedString {
+    /// Leaks `s`, returning it as a `&'static str`.
+    pub fn leak(s: String) -> LeakedString {
+        LeakedString(Box::leak(s.into_boxed_str()))
+    }
+}
+
+impl Deref for LeakedString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.0
+    }
+}
+
+impl Arbitrary for LeakedString {
+    fn arbitrary(g: &mut Gen) -> LeakedString {
+        LeakedString::leak(String::arbitrary(g))
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = LeakedString>> {
+        Box::new(self.0.to_string().shrink().map(LeakedString::leak))
+    }
+}
+
+/// The same as for `String`.
+impl DistributionInfo for LeakedString {
+    fn special_case_probability() -> f64 {
+        String::special_case_probability()
+    }
+
+    fn respects_size() -> bool {
+        String::respects_size()
+    }
+}
+
+/// A range whose `start` is never greater than its `end`.
+///
+/// `R` is either `Range<T>` or `RangeInclusive<T>`, which generate both
+/// bounds independently, so roughly half of all generated ranges are
+/// inverted. Properties that only make sense for well-formed ranges can take
+/// a `SortedRange<R>` instead of discarding those inputs. The bounds are
+/// generated independently and then sorted, and shrinking only yields ranges
+/// that keep `start <= end`.
+///
+/// Note that `start == end` is permitted, so a `SortedRange<Range<T>>` may
+/// be empty. Use `NonEmptyRange<T>` for ranges with `start < end`. Since an
+/// inclusive range with `start == end` contains one element, a
+/// `SortedRange<RangeInclusive<T>>` is never empty.
+///
+/// This is the recommended input for properties of APIs that take a range,
+/// many of which panic on inverted ranges.
+///
+/// # Example
+///
+/// ```rust
+/// use std::ops::Range;
+///
+/// use quickcheck::{quickcheck, wrappers::SortedRange};
+///
+/// fn prop(xs: Vec<u8>, r: SortedRange<Range<u8>>) -> bool {
+///     let SortedRange(r) = r;
+///     let end = (r.end as usize).min(xs.len());
+///     let start = (r.start as usize).min(end);
+///     xs[start..end].len() == end - start
+/// }
+/// quickcheck(prop as fn(Vec<u8>, SortedRange<Range<u8>>) -> bool);
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SortedRange<R>(pub R);
+
+/// A `Range<T>` whose `start` is never greater than its `end`.
+pub type OrderedRange<T> = SortedRange<Range<T>>;
+
+/// A `RangeInclusive<T>` whose `start` is never greater than its `end`.
+pub type OrderedRangeInclusive<T> = SortedRange<RangeInclusive<T>>;
+
+/// Returns two arbitrary values of `T`, the smaller one first.
+fn sorted_pair<T: Arbitrary + Ord>(g: &mut Gen) -> (T, T) {
+    let (a, b): (T, T) = Arbitrary::arbitrary(g);
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+impl<T: Arbitrary + Ord> Arbitrary for SortedRange<Range<T>> {
+    fn arbitrary(g: &mut Gen) -> SortedRange<Range<T>> {
+        let (start, end) = sorted_pair(g);
+        SortedRange(start..end)
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = SortedRange<Range<T>>>> {
+        Box::new(self.0.shrink().filter(|r| r.start <= r.end).map(SortedRange))
+    }
+}
+
+impl<T: Arbitrary + Ord> Arbitrary for SortedRange<RangeInclusive<T>> {
+    fn arbitrary(g: &mut Gen) -> SortedRange<RangeInclusive<T>> {
+        let (start, end) = sorted_pair(g);
+        SortedRange(start..=end)
+    }
+
+    fn shrink(
+        &self,
+    ) -> Box<dyn Iterator<Item = SortedRange<RangeInclusive<T>>>> {
+        Box::new(
+            self.0.shrink().filter(|r| r.start() <= r.end()).map(SortedRange),
+        )
+    }
+}
+
+/// A `Range<T>` whose `start` is always less than its `end`.
+///
+/// Unlike `SortedRange<Range<T>>`, the range is never empty. The bounds are
+/// generated independently and sorted, and are generated again if they are
+/// equal. Shrinking only yields ranges that keep `start < end`.
+///
+/// # Panics
+///
+/// Generating a value panics if no two distinct values of `T` are
+/// generated within 100 attempts, e.g., for `NonEmptyRange<()>`.
+///
+/// # Example
+///
+/// ```rust
+/// use quickcheck::{quickcheck, wrappers::NonEmptyRange};
+///
+/// fn prop(r: NonEmptyRange<u32>) -> bool {
+///     let NonEmptyRange(r) = r;
+///     r.clone().next() == Some(r.start)
+/// }
+/// quickcheck(prop as fn(NonEmptyRange<u32>) -> bool);
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct NonEmptyRange<T>(pub Range<T>);
+
+impl<T: Arbitrary + Ord> Arbitrary for NonEmptyRange<T> {
+    fn arbitrary(g: &mut Gen) -> NonEmptyRange<T> {
+        let (a, b): (T, T) = g
+            .such_that(100, <(T, T)>::arbitrary, |(a, b)| a != b)
+            .unwrap_or_else(|| {
+                panic!(
+                    "could not generate two distinct values of {}",
+                    type_name::<T>()
+                )
+            });
+        if a < b {
+            NonEmptyRange(a..b)
+        } else {
+            NonEmptyRange(b..a)
+        }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = NonEmptyRange<T>>> {
+        Box::new(
+            self.0.shrink().filter(|r| r.start < r.end).map(NonEmptyRange),
+        )
+    }
+}
+
+/// A `Bound<T>` that is never `Bound::Unbounded`.
+///
+/// `Bound<T>` generates `Unbounded` a third of the time, which is invalid
+/// for properties of ranges that must be finite. This generates `Included`
+/// and `Excluded` with equal probability instead, and shrinks the bound's
+/// value without changing its variant.
+///
+/// # Example
+///
+/// ```rust
+/// use std::ops::Bound;
+///
+/// use quickcheck::{quickcheck, wrappers::BoundedBound};
+///
+/// fn prop(b: BoundedBound<u8>) -> bool {
+///     b.0 != Bound::Unbounded
+/// }
+/// quickcheck(prop as fn(BoundedBound<u8>) -> bool);
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BoundedBound<T>(pub Bound<T>);
+
+impl<T: Arbitrary> Arbitrary for BoundedBound<T> {
+    fn arbitrary(g: &mut Gen) -> BoundedBound<T> {
+        if g.gen() {
+            BoundedBound(Bound::Included(T::arbitrary(g)))
+        } else {
+            BoundedBound(Bound::Excluded(T::arbitrary(g)))
+        }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = BoundedBound<T>>> {
+        Box::new(self.0.shrink().map(BoundedBound))
+    }
+}
+
+/// Probabilities that `Probability` generates more often than others.
+pub(crate) const PROBABILITY_PROBLEM_VALUES: &[f64] =
+    &[0.0, 1.0, 0.5, 1e-12, 1.0 - 1e-12];
+
+/// A probability, which is a `f64` in the range `0.0..=1.0`.
+///
+/// Besides values spread uniformly over the range, the endpoints, `0.5` and
+/// values within `1e-12` of the endpoints are generated fairly often.
+/// Probabilities shrink toward `0.5`, and then toward either endpoint.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Probability(f64);
+
+impl Probability {
+    /// Returns the probability as a `f64`.
+    pub fn get(self) -> f64 {
+        self.0
+    }
+}
+
+impl Deref for Probability {
+    type Target = f64;
+
+    fn deref(&self) -> &f64 {
+        &self.0
+    }
+}
+
+impl Arbitrary for Probability {
+    fn arbitrary(g: &mut Gen) -> Probability {
+        match g.gen_value_range(0..4) {
+            0 => Probability(*g.choose(PROBABILITY_PROBLEM_VALUES).unwrap()),
+            _ => Probability(g.gen_value_range(0.0..=1.0)),
+        }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Probability>> {
+        let p = self.0;
+        if p == 0.5 {
+            return empty_shrinker();
+        }
+        let mut shrunk = vec![0.5];
+        if p != 0.0 && p != 1.0 {
+            shrunk.push(0.0);
+            shrunk.push(1.0);
+            let closer = p + (0.5 - p) / 2.0;
+            if closer != p && closer != 0.5 {
+                shrunk.push(closer);
+            }
+        }
+        Box::new(shrunk.into_iter().map(Probability))
+    }
+}
+
+/// A fraction with a non-zero denominator.
+///
+/// Both reduced fractions, such as `2/3`, and fractions that aren't in
+/// lowest terms, such as `4/6`, are generated. The numerator may be greater
+/// than the denominator. Ratios shrink toward `0/1` and `1/1`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Ratio {
+    numerator: u32,
+    denominator: NonZeroU32,
+}
+
+impl Ratio {
+    fn new(numerator: u32, denominator: u32) -> Option<Ratio> {
+        let denominator = NonZeroU32::new(denominator)?;
+        Some(Ratio { numerator, denominator })
+    }
+
+    /// Returns the numerator of this ratio.
+    pub fn numerator(&self) -> u32 {
+        self.numerator
+    }
+
+    /// Returns the denominator of this ratio.
+    pub fn denominator(&self) -> NonZeroU32 {
+        self.denominator
+    }
+
+    /// Returns the value of this ratio as a `f64`.
+    pub fn to_f64(&self) -> f64 {
+        self.numerator as f64 / self.denominator.get() as f64
+    }
+}
+
+/// Returns the greatest common divisor of `a` and `b`.
+pub(crate) fn gcd(mut a: u32, mut b: u32) -> u32 {
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    a
+}
+
+impl Arbitrary for Ratio {
+    fn arbitrary(g: &mut Gen) -> Ratio {
+        match g.gen_value_range(0..4) {
+            // A small fraction scaled so that it isn't in lowest terms.
+            0 => {
+                let factor = g.gen_value_range(2..1_000);
+                let numerator = g.gen_value_range(0..1_000) * factor;
+                let denominator = g.gen_value_range(1..1_000) * factor;
+                Ratio::new(numerator, denominator).unwrap()
+            }
+            _ => Ratio {
+                numerator: Arbitrary::arbitrary(g),
+                denominator: Arbitrary::arbitrary(g),
+            },
+        }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Ratio>> {
+        let (n, d) = (self.numerator, self.denominator.get());
+        if (n, d) == (0, 1) {
+            return empty_shrinker();
+        }
+        let mut first = vec![(0, 1)];
+        if (n, d) != (1, 1) {
+            first.push((1, 1));
+            let divisor = gcd(n, d);
+            if divisor > 1 {
+                first.push((n / divisor, d / divisor));
+            }
+        }
+        let rest: Box<dyn Iterator<Item = (u32, u32)>> =
+            if (n, d) == (1, 1) { empty_shrinker() } else { (n, d).shrink() };
+        Box::new(
+            first
+                .into_iter()
+                .chain(rest)
+                .filter_map(|(n, d)| Ratio::new(n, d)),
+        )
+    }
+}
+
+/// Sorted ranges have no special cases of their own.
+impl<T: DistributionInfo + Ord> DistributionInfo for SortedRange<Range<T>> {
+    fn respects_size() -> bool {
+        T::respects_size()
+    }
+}
+
+impl<T> DistributionInfo for SortedRange<RangeInclusive<T>>
+where
+    T: DistributionInfo + Ord,
+{
+    fn respects_size() -> bool {
+        T::respects_size()
+    }
+}
+
+impl<T: DistributionInfo + Ord> DistributionInfo for NonEmptyRange<T> {
+    fn respects_size() -> bool {
+        T::respects_size()
+    }
+}
+
+/// The same as for `T`.
+impl<T: DistributionInfo> DistributionInfo for BoundedBound<T> {
+    fn special_case_probability() -> f64 {
+        T::special_case_probability()
+    }
+
+    fn respects_size() -> bool {
+        T::respects_size()
+    }
+}
+
+/// The probability is that of the endpoints, `0.5` and the values near the
+/// endpoints.
+impl DistributionInfo for Probability {
+    fn special_case_probability() -> f64 {
+        0.25
+    }
+}
+
+/// The probability is that of scaled small fractions, which aren't in
+/// lowest terms.
+impl DistributionInfo for Ratio {
+    fn special_case_probability() -> f64 {
+        0.25
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, HashSet};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::{
+        gcd, LazyArbitrary, LeakedString, Probability, Ratio, ShrinkableVec,
+        TotalF32, TotalF64, PROBABILITY_PROBLEM_VALUES,
+    };
+    use crate::{Arbitrary, Gen, QuickCheck};
+
+    #[test]
+    fn total_order() {
+        let mut xs: Vec<TotalF64> = [1.0, f64::NAN, -0.0, f64::INFINITY, 0.0]
+            .iter()
+            .map(|&x| TotalF64(x))
+            .collect();
+        xs.sort();
+        assert_eq!(
+            format!("{:?}", xs),
+            "[-0.0, 0.0, 1.0, inf, NaN]".to_string()
+        );
+        assert_eq!(TotalF32(f32::NAN), TotalF32(f32::NAN));
+        assert_ne!(TotalF32(0.0), TotalF32(-0.0));
+    }
+
+    #[test]
+    fn btreemap_keys_round_trip() {
+        let mut g = Gen::new(50);
+        for _ in 0..100 {
+            let m = BTreeMap::<TotalF64, u8>::arbitrary(&mut g);
+            let copy: BTreeMap<TotalF64, u8> =
+                m.iter().map(|(&k, &v)| (TotalF64(*k), v)).collect();
+            assert_eq!(m, copy);
+        }
+    }
+
+    #[test]
+    fn nan_keys_are_generated() {
+        let mut g = Gen::new(50);
+        let has_nan = (0..100).any(|_| {
+            HashSet::<TotalF32>::arbitrary(&mut g).iter().any(|x| x.is_nan())
+        });
+        assert!(has_nan);
+    }
+
+    #[test]
+    fn shrinking_nan_keys() {
+        let mut m = BTreeMap::new();
+        m.insert(TotalF64(f64::NAN), 1u8);
+        m.insert(TotalF64(2.0), 2);
+        m.insert(TotalF64(f64::NEG_INFINITY), 3);
+        for s in m.shrink() {
+            assert!(s.len() <= m.len());
+            let keys: Vec<_> = s.keys().collect();
+            assert!(keys.windows(2).all(|w| w[0] < w[1]));
+        }
+        assert!(m.shrink().any(|s| !s.keys().any(|k| k.is_nan())));
+    }
+
+    static GENERATED: AtomicUsize = AtomicUsize::new(0);
+
+    /// A value that counts how often it is generated.
+    #[derive(Clone, Debug)]
+    struct Expensive;
+
+    impl Arbitrary for Expensive {
+        fn arbitrary(_: &mut Gen) -> Expensive {
+            GENERATED.fetch_add(1, Ordering::SeqCst);
+            Expensive
+        }
+    }
+
+    #[test]
+    fn lazy_values_are_only_generated_when_used() {
+        fn plain(_: bool, _: Expensive) -> bool {
+            true
+        }
+        fn lazy(use_it: bool, mut x: LazyArbitrary<Expensive>) -> bool {
+            if use_it {
+                x.get();
+                x.get();
+            }
+            x.is_forced() == use_it
+        }
+
+        GENERATED.store(0, Ordering::SeqCst);
+        QuickCheck::new()
+            .tests(100)
+            .quickcheck(plain as fn(bool, Expensive) -> bool);
+        assert_eq!(GENERATED.load(Ordering::SeqCst), 100);
+
+        GENERATED.store(0, Ordering::SeqCst);
+        QuickCheck::new()
+            .tests(100)
+            .quickcheck(lazy as fn(bool, LazyArbitrary<Expensive>) -> bool);
+        let generated = GENERATED.load(Ordering::SeqCst);
+        assert!(0 < generated && generated < 100, "{}", generated);
+    }
+
+    #[test]
+    fn lazy_clones_generate_the_same_value() {
+        let mut g = Gen::new(50);
+        let mut x = LazyArbitrary::<Vec<u32>>::arbitrary(&mut g);
+        let mut y = x.clone();
+        let unforced = format!("{:?}", x);
+        assert!(!x.is_forced());
+        assert_eq!(x.get(), y.get());
+        assert_eq!(unforced, format!("{:?}", y.get()));
+    }
+
+    #[test]
+    fn lazy_values_shrink() {
+        fn prop(mut x: LazyArbitrary<u32>) -> bool {
+            *x.get() < 10
+        }
+        let witness = QuickCheck::new()
+            .run_property(prop as fn(LazyArbitrary<u32>) -> bool);
+        assert_eq!(witness, Some(vec!["10".to_string()]));
+    }
+
+    #[test]
+    fn lazy_witnesses_show_the_value() {
+        fn prop(mut x: LazyArbitrary<bool>) -> bool {
+            !*x.get()
+        }
+        let witness = QuickCheck::new()
+            .run_property(prop as fn(LazyArbitrary<bool>) -> bool);
+        assert_eq!(witness, Some(vec!["true".to_string()]));
+    }
+
+    #[test]
+    fn shrinkable_vec_candidates() {
+        let shrunk: Vec<Vec<u8>> = ShrinkableVec(vec![3, 1, 3, 2])
+            .shrink()
+            .take(5)
+            .map(|xs| xs.0)
+            .collect();
+        assert_eq!(
+            shrunk,
+            vec![
+                vec![1, 2, 3, 3],
+                vec![3, 1, 2],
+                vec![2, 3, 1, 3],
+                vec![3, 1],
+                vec![3, 2],
+            ]
+        );
+        let sorted = ShrinkableVec(vec![1u8, 2]);
+        assert!(sorted.shrink().all(|xs| xs < sorted || xs.len() < 2));
+    }
+
+    #[test]
+    fn shrinkable_vec_shrinks_duplicates_quickly() {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        fn distinct(xs: &[u16]) -> bool {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            xs.iter().collect::<HashSet<_>>().len() == xs.len()
+        }
+        fn prop_plain(xs: Vec<u16>) -> bool {
+            distinct(&xs)
+        }
+        fn prop_shrinkable(xs: ShrinkableVec<u16>) -> bool {
+            distinct(&xs)
+        }
+        /// Returns whether `witness` formats two equal elements.
+        fn is_pair(witness: &str) -> bool {
+            let start = witness.find('[').unwrap();
+            let end = witness.find(']').unwrap();
+            let xs: Vec<&str> = witness[start + 1..end].split(", ").collect();
+            xs.len() == 2 && xs[0] == xs[1]
+        }
+
+        for seed in 0..10 {
+            let qc = || QuickCheck::new().gen(Gen::from_seed(100, seed));
+            CALLS.store(0, Ordering::SeqCst);
+            let plain =
+                qc().run_property(prop_plain as fn(Vec<u16>) -> bool).unwrap();
+            let plain_calls = CALLS.load(Ordering::SeqCst);
+            CALLS.store(0, Ordering::SeqCst);
+            let shrinkable = qc()
+                .run_property(
+                    prop_shrinkable as fn(ShrinkableVec<u16>) -> bool,
+                )
+                .unwrap();
+            let calls = CALLS.load(Ordering::SeqCst);
+            assert!(is_pair(&plain[0]), "{:?}", plain);
+            assert!(is_pair(&shrinkable[0]), "{:?}", shrinkable);
+            assert!(calls < plain_calls, "{} >= {}", calls, plain_calls);
+        }
+    }
+
+    #[test]
+    fn leaked_strings() {
+        fn register(name: &'static str) -> &'static str {
+            name
+        }
+        fn prop(name: LeakedString) -> bool {
+            register(name.0).len() == name.len()
+        }
+        crate::quickcheck(prop as fn(LeakedString) -> bool);
+
+        let shrunk: Vec<String> = LeakedString::leak("ab".to_string())
+            .shrink()
+            .map(|s| s.to_string())
+            .collect();
+        let expected: Vec<String> = "ab".to_string().shrink().collect();
+        assert_eq!(shrunk, expected);
+    }
+
+    #[test]
+    fn probabilities() {
+        fn valid(p: &Probability) -> bool {
+            (0.0..=1.0).contains(&p.get())
+        }
+        fn prop(p: Probability) -> bool {
+            valid(&p) && p.shrink().all(|s| valid(&s))
+        }
+        crate::quickcheck(prop as fn(Probability) -> bool);
+
+        let mut g = Gen::new(100);
+        let arbys: Vec<Probability> =
+            (0..10_000).map(|_| Probability::arbitrary(&mut g)).collect();
+        for &p in PROBABILITY_PROBLEM_VALUES {
+            assert!(arbys.iter().any(|a| a.get() == p), "{} missing", p);
+        }
+
+        let mut p = Probability(0.123);
+        while let Some(smaller) = p.shrink().last() {
+            p = smaller;
+        }
+        assert_eq!(p.get(), 0.5);
+    }
+
+    #[test]
+    fn ratios() {
+        let mut g = Gen::new(100);
+        let arbys: Vec<Ratio> =
+            (0..10_000).map(|_| Ratio::arbitrary(&mut g)).collect();
+        assert!(arbys.iter().any(|r| r.numerator() > r.denominator().get()));
+        assert!(arbys.iter().any(|r| r.numerator() < r.denominator().get()));
+        assert!(arbys
+            .iter()
+            .any(|r| gcd(r.numerator(), r.denominator().get()) > 1));
+        assert!(arbys
+            .iter()
+            .any(|r| gcd(r.numerator(), r.denominator().get()) == 1));
+
+        let r = Ratio::new(4, 6).unwrap();
+        let shrunk: Vec<Ratio> = r.shrink().take(3).collect();
+        assert_eq!(
+            shrunk,
+            vec![
+                Ratio::new(0, 1).unwrap(),
+                Ratio::new(1, 1).unwrap(),
+                Ratio::new(2, 3).unwrap(),
+            ],
+        );
+        assert_eq!(
+            Ratio::new(1, 1).unwrap().shrink().collect::<Vec<_>>(),
+            vec![Ratio::new(0, 1).unwrap()],
+        );
+        assert_eq!(Ratio::new(0, 1).unwrap().shrink().count(), 0);
+    }
+}
@@ -0,0 +1,39 @@
+//! Checks the process-wide defaults for `QuickCheck::new`.
+//!
+//! This lives in its own test binary, since the defaults can only be set
+//! once per process, and it sets environment variables that every
+//! `QuickCheck::new` reads.
+
+use std::env;
+
+use quickcheck::{
+    set_default_gen_size, set_default_tests, Config, QuickCheck,
+};
+
+#[test]
+fn defaults() {
+    env::remove_var("QUICKCHECK_TESTS");
+    env::remove_var("QUICKCHECK_GENERATOR_SIZE");
+
+    assert_eq!(set_default_tests(5), Ok(()));
+    assert_eq!(set_default_gen_size(7), Ok(()));
+    assert_eq!(set_default_tests(6), Err(6));
+    assert_eq!(set_default_gen_size(8), Err(8));
+
+    fn prop(xs: Vec<u8>) -> bool {
+        xs.len() < 7
+    }
+    let passed =
+        QuickCheck::new().quicktest(prop as fn(Vec<u8>) -> bool).unwrap();
+    assert_eq!(passed, 5);
+    let config = Config::default();
+    assert_eq!((config.tests, config.gen_size), (5, 7));
+
+    // The environment takes precedence.
+    env::set_var("QUICKCHECK_TESTS", "9");
+    env::set_var("QUICKCHECK_GENERATOR_SIZE", "11");
+    let config = Config::default();
+    assert_eq!((config.tests, config.gen_size), (9, 11));
+    env::remove_var("QUICKCHECK_TESTS");
+    env::remove_var("QUICKCHECK_GENERATOR_SIZE");
+}
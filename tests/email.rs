@@ -0,0 +1,154 @@
+//! A worked example of an `Arbitrary` impl for a type with invariants.
+//!
+//! An `Email` has a non-empty local part made of lowercase letters and
+//! digits, and a domain of two to four non-empty labels made of lowercase
+//! letters. Generation only produces valid emails, and shrinking only yields
+//! valid emails that are strictly smaller, so shrinking always terminates.
+//!
+//! The tests check the distribution of generated emails and the quality of
+//! the shrinker: a broken property must always shrink to the same minimal
+//! witness. If a change to the generators or shrinkers of this crate makes
+//! that witness worse, these tests fail.
+
+use quickcheck::{Arbitrary, Gen, QuickCheck};
+
+const LOCAL_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+const LABEL_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+
+#[derive(Clone, Debug, PartialEq)]
+struct Email {
+    local: String,
+    domain: Vec<String>,
+}
+
+impl Email {
+    fn is_valid(&self) -> bool {
+        let made_of = |s: &str, chars: &[u8]| {
+            !s.is_empty() && s.bytes().all(|b| chars.contains(&b))
+        };
+        made_of(&self.local, LOCAL_CHARS)
+            && (2..=4).contains(&self.domain.len())
+            && self.domain.iter().all(|label| made_of(label, LABEL_CHARS))
+    }
+
+    fn address(&self) -> String {
+        format!("{}@{}", self.local, self.domain.join("."))
+    }
+
+    /// The number of characters, and the sum of their positions in their
+    /// alphabets. Every shrink candidate is smaller by this measure.
+    fn measure(&self) -> (usize, usize) {
+        let parts = Some(&self.local).into_iter().chain(&self.domain);
+        let chars = parts.clone().map(|s| s.len()).sum();
+        let positions = parts.flat_map(|s| to_positions(s)).map(usize::from);
+        (chars, positions.sum())
+    }
+}
+
+/// Generates a string of 1 to 10 characters from `chars`.
+fn gen_label(g: &mut Gen, chars: &[u8]) -> String {
+    let len = 1 + usize::arbitrary(g) % 10;
+    (0..len).map(|_| char::from(*g.choose(chars).unwrap())).collect()
+}
+
+/// Returns the position of each character of `s` in its alphabet. Since
+/// `LABEL_CHARS` is a prefix of `LOCAL_CHARS`, the positions are the same
+/// in both alphabets.
+fn to_positions(s: &str) -> Vec<u8> {
+    s.bytes()
+        .map(|b| LOCAL_CHARS.iter().position(|&c| c == b).unwrap() as u8)
+        .collect()
+}
+
+fn from_positions(positions: &[u8]) -> String {
+    positions
+        .iter()
+        .map(|&i| char::from(LOCAL_CHARS[usize::from(i)]))
+        .collect()
+}
+
+impl Arbitrary for Email {
+    fn arbitrary(g: &mut Gen) -> Email {
+        let labels = 2 + usize::arbitrary(g) % 3;
+        Email {
+            local: gen_label(g, LOCAL_CHARS),
+            domain: (0..labels).map(|_| gen_label(g, LABEL_CHARS)).collect(),
+        }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Email>> {
+        // Shrinking the positions of the characters rather than the
+        // characters themselves means that every character shrinks toward
+        // 'a' and stays in its alphabet. The shrinker of the tuple is
+        // finite and only yields smaller values, so filtering out invalid
+        // candidates keeps it that way.
+        let positions = (
+            to_positions(&self.local),
+            self.domain.iter().map(|label| to_positions(label)).collect(),
+        );
+        Box::new(
+            positions
+                .shrink()
+                .map(|(local, domain): (Vec<u8>, Vec<Vec<u8>>)| Email {
+                    local: from_positions(&local),
+                    domain: domain.iter().map(|l| from_positions(l)).collect(),
+                })
+                .filter(Email::is_valid),
+        )
+    }
+}
+
+/// A broken way of finding the domain of an address, which drops all but
+/// the last two labels of the domain.
+fn domain_of(address: &str) -> String {
+    let domain = address.split('@').nth(1).unwrap();
+    let mut labels: Vec<&str> = domain.rsplit('.').take(2).collect();
+    labels.reverse();
+    labels.join(".")
+}
+
+fn prop_domain_of(email: Email) -> bool {
+    domain_of(&email.address()) == email.domain.join(".")
+}
+
+#[test]
+fn generated_emails_are_valid_and_varied() {
+    let mut g = Gen::new(100);
+    let emails: Vec<Email> =
+        (0..1_000).map(|_| Email::arbitrary(&mut g)).collect();
+    assert!(emails.iter().all(Email::is_valid));
+    for labels in 2..=4 {
+        assert!(emails.iter().any(|e| e.domain.len() == labels));
+    }
+    for len in &[1, 10] {
+        assert!(emails.iter().any(|e| e.local.len() == *len));
+    }
+    let locals: String = emails.iter().map(|e| e.local.as_str()).collect();
+    assert!(locals.bytes().any(|b| b.is_ascii_digit()));
+    assert!(locals.bytes().any(|b| b.is_ascii_lowercase()));
+}
+
+#[test]
+fn shrink_candidates_are_valid_and_smaller() {
+    let mut g = Gen::new(100);
+    for _ in 0..100 {
+        let email = Email::arbitrary(&mut g);
+        for candidate in email.shrink() {
+            assert!(candidate.is_valid(), "{:?}", candidate);
+            assert!(candidate.measure() < email.measure(), "{:?}", candidate);
+        }
+    }
+}
+
+#[test]
+fn broken_property_shrinks_to_minimal_witness() {
+    for _ in 0..20 {
+        let witness = QuickCheck::new()
+            .run_property(prop_domain_of as fn(Email) -> bool)
+            .expect("domain_of is broken for domains with three labels");
+        assert_eq!(
+            witness,
+            vec![r#"Email { local: "a", domain: ["a", "a", "a"] }"#]
+        );
+    }
+}
@@ -0,0 +1,43 @@
+//! Checks that a failure can be reproduced from its panic message.
+//!
+//! This lives in its own test binary, since it sets environment variables
+//! that every `QuickCheck::new` reads.
+
+use std::env;
+use std::panic;
+
+use quickcheck::QuickCheck;
+
+fn prop(xs: Vec<u8>) -> bool {
+    xs.len() % 7 != 3
+}
+
+fn failure_message(qc: QuickCheck) -> String {
+    let mut qc = panic::AssertUnwindSafe(qc);
+    let err = panic::catch_unwind(move || {
+        qc.quickcheck(prop as fn(Vec<u8>) -> bool)
+    })
+    .unwrap_err();
+    err.downcast_ref::<String>().unwrap().clone()
+}
+
+fn env_value<'a>(msg: &'a str, var: &str) -> &'a str {
+    let start = msg.find(&format!("{}=", var)).unwrap() + var.len() + 1;
+    msg[start..].split_whitespace().next().unwrap()
+}
+
+#[test]
+fn reproduce_from_failure_message() {
+    env::remove_var("QUICKCHECK_SEED");
+    env::remove_var("QUICKCHECK_START_CASE");
+    let first = failure_message(QuickCheck::new());
+
+    env::set_var("QUICKCHECK_SEED", env_value(&first, "QUICKCHECK_SEED"));
+    env::set_var(
+        "QUICKCHECK_START_CASE",
+        env_value(&first, "QUICKCHECK_START_CASE"),
+    );
+    // With only one test to run, the failure must be found immediately.
+    let reproduced = failure_message(QuickCheck::new().tests(1));
+    assert_eq!(reproduced, first);
+}
@@ -0,0 +1,4 @@
+/*!
+This crate only exists to run quickcheck's tests on wasm32-unknown-unknown,
+with `wasm-pack test --node wasm-test`. See `tests/wasm.rs`.
+*/
@@ -0,0 +1,44 @@
+#![cfg(target_arch = "wasm32")]
+
+use std::collections::{BTreeSet, HashMap};
+use std::ffi::{CString, OsString};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use quickcheck::{Arbitrary, Gen, QuickCheck};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn generate_and_shrink<A: Arbitrary>(g: &mut Gen) {
+    for _ in 0..100 {
+        A::arbitrary(g).shrink().take(10).for_each(drop);
+    }
+}
+
+#[wasm_bindgen_test]
+fn std_types() {
+    let mut g = Gen::new(100);
+    generate_and_shrink::<(bool, char, u8, i64, u128, f32, f64)>(&mut g);
+    generate_and_shrink::<(String, OsString, CString, PathBuf)>(&mut g);
+    generate_and_shrink::<(Duration, SystemTime, SocketAddr)>(&mut g);
+    generate_and_shrink::<(Vec<u8>, BTreeSet<u8>, HashMap<u8, u8>)>(&mut g);
+    generate_and_shrink::<(Option<u8>, Result<u8, i8>, [u8; 32])>(&mut g);
+}
+
+#[wasm_bindgen_test]
+fn passing_property() {
+    fn prop(xs: Vec<u32>) -> bool {
+        let rev: Vec<u32> = xs.iter().rev().rev().cloned().collect();
+        rev == xs
+    }
+    quickcheck::quickcheck(prop as fn(Vec<u32>) -> bool);
+}
+
+#[wasm_bindgen_test]
+fn failing_property_is_shrunk() {
+    fn prop(xs: Vec<u8>) -> bool {
+        xs.len() < 3
+    }
+    let witness = QuickCheck::new().run_property(prop as fn(Vec<u8>) -> bool);
+    assert_eq!(witness, Some(vec!["[0, 0, 0]".to_string()]));
+}